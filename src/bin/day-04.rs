@@ -1,3 +1,5 @@
+use aoc_2022::RangeSet;
+
 const MY_INPUT: &str = include_str!("../../inputs/day-04.txt");
 
 fn main() {
@@ -23,16 +25,17 @@ fn parse_input(s: &str) -> Input {
         .collect()
 }
 
+fn to_set((start, end): (u8, u8)) -> RangeSet<u8> {
+    RangeSet::from(start..=end)
+}
+
 fn contains(range1: (u8, u8), range2: (u8, u8)) -> bool {
-    let (a, b) = range1;
-    let (c, d) = range2;
-    (a <= c && d <= b) || (c <= a && b <= d)
+    let (a, b) = (to_set(range1), to_set(range2));
+    a.is_superset(&b) || b.is_superset(&a)
 }
 
 fn overlaps(range1: (u8, u8), range2: (u8, u8)) -> bool {
-    let (a, b) = range1;
-    let (c, d) = range2;
-    contains(range1, range2) || (a <= d && d <= b) || (a <= c && c <= b)
+    !to_set(range1).is_disjoint(&to_set(range2))
 }
 
 fn part1(input: &Input) -> i32 {
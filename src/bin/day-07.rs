@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use aoc_2022::{Interner, Symbol};
+
 const MY_INPUT: &str = include_str!("../../inputs/day-07.txt");
 
 fn main() {
@@ -11,45 +13,45 @@ fn main() {
 type Input = Vec<Cmd>;
 
 enum Cmd {
-    Cd(&'static str),
+    CdRoot,
+    CdUp,
+    Cd(Symbol),
     Ls(Vec<Entry>),
 }
 
 enum Entry {
-    Dir(&'static str),
-    File(&'static str, usize),
+    Dir(Symbol),
+    File(Symbol, usize),
 }
 
 enum Fs {
-    Dir(HashMap<&'static str, Fs>),
+    Dir(HashMap<Symbol, Fs>),
     File(usize),
 }
 
 fn build_fs(input: &Input) -> Fs {
     let mut root_dir = HashMap::new();
-    let mut cwd: Vec<&str> = vec![];
+    let mut cwd: Vec<Symbol> = vec![];
     for cmd in input {
         match cmd {
-            Cmd::Cd(x) => match *x {
-                "/" => cwd.clear(),
-                ".." => {
-                    cwd.pop();
-                }
-                _ => {
-                    let mut parent = &mut root_dir;
-                    for dir in cwd.iter() {
-                        let next_parent = parent.get_mut(dir).unwrap();
-                        parent = match next_parent {
-                            Fs::Dir(children) => children,
-                            Fs::File(_) => panic!("file can't be a parent"),
-                        }
-                    }
-                    if !parent.contains_key(x) {
-                        parent.insert(x, Fs::Dir(HashMap::new()));
+            Cmd::CdRoot => cwd.clear(),
+            Cmd::CdUp => {
+                cwd.pop();
+            }
+            Cmd::Cd(x) => {
+                let mut parent = &mut root_dir;
+                for dir in cwd.iter() {
+                    let next_parent = parent.get_mut(dir).unwrap();
+                    parent = match next_parent {
+                        Fs::Dir(children) => children,
+                        Fs::File(_) => panic!("file can't be a parent"),
                     }
-                    cwd.push(x);
                 }
-            },
+                if !parent.contains_key(x) {
+                    parent.insert(*x, Fs::Dir(HashMap::new()));
+                }
+                cwd.push(*x);
+            }
             Cmd::Ls(entries) => {
                 let mut parent = &mut root_dir;
                 for dir in cwd.iter() {
@@ -63,11 +65,11 @@ fn build_fs(input: &Input) -> Fs {
                     match e {
                         Entry::Dir(name) => {
                             if !parent.contains_key(name) {
-                                parent.insert(name, Fs::Dir(HashMap::new()));
+                                parent.insert(*name, Fs::Dir(HashMap::new()));
                             }
                         }
                         Entry::File(name, size) => {
-                            parent.insert(name, Fs::File(*size));
+                            parent.insert(*name, Fs::File(*size));
                         }
                     }
                 }
@@ -77,7 +79,8 @@ fn build_fs(input: &Input) -> Fs {
     Fs::Dir(root_dir)
 }
 
-fn parse_input(s: &'static str) -> Input {
+fn parse_input(s: &str) -> Input {
+    let mut names = Interner::new();
     s.split("$ ")
         .skip(1)
         .map(|cmd| {
@@ -85,16 +88,18 @@ fn parse_input(s: &'static str) -> Input {
             let cmd = lines.next().unwrap();
             let cmd: Vec<_> = cmd.split_ascii_whitespace().collect();
             match cmd[..] {
-                ["cd", dir] => Cmd::Cd(dir),
+                ["cd", "/"] => Cmd::CdRoot,
+                ["cd", ".."] => Cmd::CdUp,
+                ["cd", dir] => Cmd::Cd(names.intern(dir)),
                 ["ls"] => {
                     let entries = lines
                         .map(|line| {
                             let (a, b) = line.split_once(' ').unwrap();
                             match a {
-                                "dir" => Entry::Dir(b),
+                                "dir" => Entry::Dir(names.intern(b)),
                                 _ => {
                                     let size = a.parse().unwrap();
-                                    Entry::File(b, size)
+                                    Entry::File(names.intern(b), size)
                                 }
                             }
                         })
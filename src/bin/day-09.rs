@@ -2,12 +2,16 @@ use std::cmp::Ordering;
 use std::collections::HashSet;
 use std::str::FromStr;
 
+use aoc_2022::record::Recorder;
+
 const MY_INPUT: &str = include_str!("../../inputs/day-09.txt");
 
 fn main() {
     let input = parse_input(MY_INPUT);
+    let mut recorder = Recorder::from_args();
     println!("part1: {}", part1(&input));
-    println!("part2: {}", part2(&input));
+    println!("part2: {}", part2(&input, &mut recorder));
+    recorder.finish().unwrap();
 }
 
 type Input = Vec<(Dir, u8)>;
@@ -103,7 +107,23 @@ fn mv_tail(head: (i32, i32), tail: &mut (i32, i32)) -> bool {
     }
 }
 
-fn part2(input: &Input) -> usize {
+// Draws the rope like the puzzle does, with y flipped so that up is up.
+fn render_rope(rope: &[(i32, i32)], visited: &HashSet<(i32, i32)>) -> Vec<((i64, i64), char)> {
+    let to_point = |(x, y): (i32, i32)| (-y as i64, x as i64);
+    let mut points: Vec<_> = visited.iter().map(|&xy| (to_point(xy), '#')).collect();
+    points.push((to_point((0, 0)), 's'));
+    for (i, &knot) in rope.iter().enumerate().rev() {
+        let c = if i == 0 {
+            'H'
+        } else {
+            char::from_digit(i as u32, 36).unwrap()
+        };
+        points.push((to_point(knot), c));
+    }
+    points
+}
+
+fn part2(input: &Input, recorder: &mut Recorder) -> usize {
     let mut visited: HashSet<(i32, i32)> = HashSet::new();
     visited.insert((0, 0));
     let mut rope = [(0, 0); 10];
@@ -126,6 +146,7 @@ fn part2(input: &Input) -> usize {
                 }
             }
         }
+        recorder.push_points(render_rope(&rope, &visited), '.');
     }
     visited.len()
 }
@@ -161,7 +182,7 @@ U 20";
     #[test]
     fn part2_sample() {
         let input = parse_input(LARGER_SAMPLE);
-        assert_eq!(part2(&input), 36);
+        assert_eq!(part2(&input, &mut Recorder::disabled()), 36);
     }
 
     #[test]
@@ -173,6 +194,6 @@ U 20";
     #[test]
     fn part2_my_input() {
         let input = parse_input(MY_INPUT);
-        assert_eq!(part2(&input), 2369);
+        assert_eq!(part2(&input, &mut Recorder::disabled()), 2369);
     }
 }
@@ -1,3 +1,4 @@
+use aoc_2022::ocr;
 use itertools::Itertools;
 use std::str::FromStr;
 
@@ -6,7 +7,10 @@ const MY_INPUT: &str = include_str!("../../inputs/day-10.txt");
 fn main() {
     let input = parse_input(MY_INPUT);
     println!("part1: {}", part1(&input));
-    println!("part2:\n{}", part2(&input));
+    println!("part2: {}", part2(&input));
+    if std::env::args().any(|arg| arg == "--show") {
+        println!("{}", draw_screen(&input));
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -68,7 +72,7 @@ fn set_pixel(cycle: i32, reg: i32, screen: &mut [u8]) {
     screen[pixel as usize] = p;
 }
 
-fn part2(input: &Input) -> String {
+fn draw_screen(input: &Input) -> String {
     let mut cycle = 1;
     let mut reg = 1;
     let mut screen = [b' '; 40 * 6];
@@ -87,6 +91,10 @@ fn part2(input: &Input) -> String {
     show_screen(&screen)
 }
 
+fn part2(input: &Input) -> String {
+    ocr::read_letters(&draw_screen(input)).unwrap_or_else(|e| panic!("{e}"))
+}
+
 fn show_screen(screen: &[u8]) -> String {
     (0..6)
         .map(|r| {
@@ -262,9 +270,9 @@ noop";
 #######       #######       #######     ";
 
     #[test]
-    fn part2_sample() {
+    fn draw_sample() {
         let input = parse_input(SAMPLE);
-        assert_eq!(PART_2_SAMPLE_OUTPUT, part2(&input));
+        assert_eq!(PART_2_SAMPLE_OUTPUT, draw_screen(&input));
     }
 
     #[test]
@@ -280,9 +288,18 @@ noop";
 #  # #  # # #  #  # #    # #  #    #    
 ###   ### #  # #  # #### #  # #### #### ";
 
+    #[test]
+    fn read_my_output() {
+        assert_eq!(
+            ocr::read_letters(PART_2_MY_OUTPUT),
+            Ok("BGKAEREZ".to_string())
+        );
+    }
+
     #[test]
     fn part2_my_input() {
         let input = parse_input(MY_INPUT);
-        assert_eq!(part2(&input), PART_2_MY_OUTPUT);
+        assert_eq!(draw_screen(&input), PART_2_MY_OUTPUT);
+        assert_eq!(part2(&input), "BGKAEREZ");
     }
 }
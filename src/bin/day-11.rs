@@ -1,4 +1,8 @@
-use itertools::Itertools;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::FromStr;
+
+use aoc_2022::math::{self, BigUint};
 
 const MY_INPUT: &str = include_str!("../../inputs/day-11.txt");
 
@@ -6,145 +10,486 @@ fn main() {
     let input = parse_input(MY_INPUT);
     println!("part1: {}", part1(&input));
     println!("part2: {}", part2(&input));
+    // `--rounds <n>`, `--relief <k|mod|none>` and `--top <n>` play by other
+    // rules, and `--big` tracks worry levels without any size limit.
+    let args: Vec<String> = std::env::args().collect();
+    let flag = |name: &str| {
+        args.windows(2)
+            .find(|pair| pair[0] == name)
+            .map(|pair| pair[1].as_str())
+    };
+    let big = args.iter().any(|arg| arg == "--big");
+    if flag("--rounds").is_some() || flag("--relief").is_some() || flag("--top").is_some() || big {
+        let rules = Rules {
+            rounds: flag("--rounds").map_or(PART1.rounds, |n| n.parse().unwrap()),
+            relief: flag("--relief").map_or(PART1.relief, |relief| match relief {
+                "mod" => Relief::Modulo,
+                "none" => Relief::None,
+                k => match k.parse().unwrap() {
+                    0 => panic!("can't divide worry levels by 0"),
+                    k => Relief::DivideBy(k),
+                },
+            }),
+            top: flag("--top").map_or(PART1.top, |n| n.parse().unwrap()),
+        };
+        let business = if big {
+            monkey_business::<BigUint>(&input, &rules)
+        } else {
+            monkey_business::<Item>(&input, &rules)
+        };
+        match business {
+            Ok(business) => println!("monkey business: {business}"),
+            Err(e) => println!("{e}"),
+        }
+    }
 }
 
 type Input = Vec<Monkey>;
 
 type Item = u64;
 
-#[derive(PartialEq, Eq, Copy, Clone)]
-enum Val {
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+/// A way of holding worry levels.
+trait Worry: Clone {
+    fn from_item(n: Item) -> Self;
+
+    /// `self op rhs`, or `None` if it would overflow, go negative or divide
+    /// by zero.
+    fn apply(&self, op: BinOp, rhs: &Self) -> Option<Self>;
+
+    fn rem(&self, m: Item) -> Item;
+}
+
+impl Worry for Item {
+    fn from_item(n: Item) -> Self {
+        n
+    }
+
+    fn apply(&self, op: BinOp, rhs: &Self) -> Option<Self> {
+        match op {
+            BinOp::Add => self.checked_add(*rhs),
+            BinOp::Sub => self.checked_sub(*rhs),
+            BinOp::Mul => self.checked_mul(*rhs),
+            BinOp::Div => self.checked_div(*rhs),
+        }
+    }
+
+    fn rem(&self, m: Item) -> Item {
+        self % m
+    }
+}
+
+impl Worry for BigUint {
+    fn from_item(n: Item) -> Self {
+        BigUint::from(n)
+    }
+
+    fn apply(&self, op: BinOp, rhs: &Self) -> Option<Self> {
+        match op {
+            BinOp::Add => Some(self + rhs),
+            BinOp::Sub => self.checked_sub(rhs),
+            BinOp::Mul => Some(self * rhs),
+            BinOp::Div => self.checked_div_rem(rhs).map(|(q, _)| q),
+        }
+    }
+
+    fn rem(&self, m: Item) -> Item {
+        self.div_rem_u64(m).1
+    }
+}
+
+/// The right hand side of a monkey's `new = ...` operation.
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Expr {
+    Old,
     Const(Item),
+    Bin(Box<Expr>, BinOp, Box<Expr>),
+}
+
+impl Expr {
+    /// The new worry level, or `None` if it would overflow, go negative or
+    /// divide by zero.
+    fn eval<W: Worry>(&self, old: &W) -> Option<W> {
+        match self {
+            Expr::Old => Some(old.clone()),
+            Expr::Const(c) => Some(W::from_item(*c)),
+            Expr::Bin(l, op, r) => l.eval(old)?.apply(*op, &r.eval(old)?),
+        }
+    }
+
+    /// Whether the expression only adds, subtracts and multiplies, so that
+    /// it can be evaluated modulo anything.
+    fn is_polynomial(&self) -> bool {
+        match self {
+            Expr::Old | Expr::Const(_) => true,
+            Expr::Bin(_, BinOp::Div, _) => false,
+            Expr::Bin(l, _, r) => l.is_polynomial() && r.is_polynomial(),
+        }
+    }
+
+    /// The new worry level modulo `m`, given the old one modulo `m`. Only
+    /// meaningful for polynomials. The sign of the real worry level is lost,
+    /// so subtraction works as it would on signed integers: unlike `eval`,
+    /// going below zero isn't an error.
+    fn eval_mod(&self, old: Item, m: Item) -> Item {
+        let m128 = m as u128;
+        match self {
+            Expr::Old => old % m,
+            Expr::Const(c) => c % m,
+            Expr::Bin(l, op, r) => {
+                let (l, r) = (l.eval_mod(old, m) as u128, r.eval_mod(old, m) as u128);
+                (match op {
+                    BinOp::Add => (l + r) % m128,
+                    BinOp::Sub => (l + m128 - r) % m128,
+                    BinOp::Mul => l * r % m128,
+                    BinOp::Div => panic!("can't divide modulo {m}"),
+                }) as Item
+            }
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = tokenize(s)?.into_iter().peekable();
+        let expr = parse_sum(&mut tokens)?;
+        match tokens.next() {
+            None => Ok(expr),
+            Some(token) => Err(format!("unexpected {token} in expression")),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token {
+    Num(Item),
     Old,
+    Op(BinOp),
+    Open,
+    Close,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Num(n) => write!(f, "{n}"),
+            Token::Old => write!(f, "old"),
+            Token::Op(BinOp::Add) => write!(f, "'+'"),
+            Token::Op(BinOp::Sub) => write!(f, "'-'"),
+            Token::Op(BinOp::Mul) => write!(f, "'*'"),
+            Token::Op(BinOp::Div) => write!(f, "'/'"),
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+        }
+    }
 }
 
-#[derive(PartialEq, Eq, Copy, Clone)]
-enum Op {
-    Plus(Val),
-    Times(Val),
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut cs = s.chars().peekable();
+    while let Some(c) = cs.next() {
+        tokens.push(match c {
+            ' ' => continue,
+            '+' => Token::Op(BinOp::Add),
+            '-' => Token::Op(BinOp::Sub),
+            '*' => Token::Op(BinOp::Mul),
+            '/' => Token::Op(BinOp::Div),
+            '(' => Token::Open,
+            ')' => Token::Close,
+            c if c.is_ascii_digit() => {
+                let mut n = c.to_string();
+                while let Some(c) = cs.next_if(char::is_ascii_digit) {
+                    n.push(c);
+                }
+                Token::Num(n.parse().map_err(|_| format!("{n} is too big"))?)
+            }
+            c if c.is_ascii_alphabetic() => {
+                let mut word = c.to_string();
+                while let Some(c) = cs.next_if(char::is_ascii_alphanumeric) {
+                    word.push(c);
+                }
+                if word != "old" {
+                    return Err(format!("unknown variable {word}"));
+                }
+                Token::Old
+            }
+            c => return Err(format!("unexpected {c:?} in expression")),
+        });
+    }
+    Ok(tokens)
+}
+
+type Tokens = Peekable<std::vec::IntoIter<Token>>;
+
+// sum := product (('+' | '-') product)*
+fn parse_sum(tokens: &mut Tokens) -> Result<Expr, String> {
+    let mut expr = parse_product(tokens)?;
+    while let Some(Token::Op(op)) =
+        tokens.next_if(|t| matches!(t, Token::Op(BinOp::Add | BinOp::Sub)))
+    {
+        expr = Expr::Bin(Box::new(expr), op, Box::new(parse_product(tokens)?));
+    }
+    Ok(expr)
+}
+
+// product := atom (('*' | '/') atom)*
+fn parse_product(tokens: &mut Tokens) -> Result<Expr, String> {
+    let mut expr = parse_atom(tokens)?;
+    while let Some(Token::Op(op)) =
+        tokens.next_if(|t| matches!(t, Token::Op(BinOp::Mul | BinOp::Div)))
+    {
+        expr = Expr::Bin(Box::new(expr), op, Box::new(parse_atom(tokens)?));
+    }
+    Ok(expr)
+}
+
+// atom := number | 'old' | '(' sum ')'
+fn parse_atom(tokens: &mut Tokens) -> Result<Expr, String> {
+    match tokens.next() {
+        Some(Token::Num(n)) => Ok(Expr::Const(n)),
+        Some(Token::Old) => Ok(Expr::Old),
+        Some(Token::Open) => {
+            let expr = parse_sum(tokens)?;
+            match tokens.next() {
+                Some(Token::Close) => Ok(expr),
+                Some(token) => Err(format!("expected ')' but found {token}")),
+                None => Err("expected ')' but the expression ended".to_string()),
+            }
+        }
+        Some(token) => Err(format!("unexpected {token} in expression")),
+        None => Err("the expression ended early".to_string()),
+    }
 }
 
 #[derive(Clone)]
 struct Monkey {
     items: Vec<Item>,
-    op: Op,
+    op: Expr,
     test_divisible: Item,
     true_branch: usize,
     false_branch: usize,
 }
 
-fn parse_input(s: &str) -> Input {
-    let monkeys = s.split("\n\n");
-    monkeys
-        .map(|monkey| {
-            let mut lines = monkey.lines();
-            lines.next(); // Monkey: line
-            let items: Vec<Item> = lines
-                .next()
-                .unwrap()
-                .split_ascii_whitespace()
-                .skip(2)
-                .map(|i| i.trim_end_matches(',').parse().unwrap())
-                .collect();
-            let op: Vec<_> = lines
-                .next()
-                .unwrap()
-                .split_ascii_whitespace()
-                .skip(4)
-                .collect();
-            let val = match op[1] {
-                "old" => Val::Old,
-                val => Val::Const(val.parse().unwrap()),
-            };
-            let op = match op[0] {
-                "+" => Op::Plus(val),
-                "*" => Op::Times(val),
-                _ => panic!("bad operator"),
-            };
-            let test_divisible: Item = lines
-                .next()
-                .unwrap()
-                .split_ascii_whitespace()
-                .nth(3)
-                .unwrap()
-                .parse()
-                .unwrap();
-            let true_branch: usize = lines
-                .next()
-                .unwrap()
-                .split_ascii_whitespace()
-                .nth(5)
-                .unwrap()
-                .parse()
-                .unwrap();
-            let false_branch: usize = lines
-                .next()
-                .unwrap()
-                .split_ascii_whitespace()
-                .nth(5)
-                .unwrap()
-                .parse()
-                .unwrap();
-            Monkey {
-                items,
-                op,
-                test_divisible,
-                true_branch,
-                false_branch,
-            }
-        })
-        .collect()
+/// Why a line of the notes couldn't be read.
+#[derive(Debug, PartialEq, Eq)]
+struct ParseError {
+    /// The monkey being described, counting from 0.
+    monkey: usize,
+    /// The line of the notes, counting from 1.
+    line: usize,
+    reason: String,
 }
 
-fn apply_op(op: Op, worry: Item) -> Item {
-    match op {
-        Op::Plus(Val::Const(c)) => worry + c,
-        Op::Plus(Val::Old) => worry * 2,
-        Op::Times(Val::Const(c)) => worry * c,
-        Op::Times(Val::Old) => worry * worry,
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "monkey {}, line {}: {}",
+            self.monkey, self.line, self.reason
+        )
     }
 }
 
-fn part1(monkeys: &Input) -> usize {
-    let mut entries_per_monkey: Vec<_> =
-        monkeys.iter().map(|monkey| monkey.items.clone()).collect();
-    let mut inspection_counts = vec![0; monkeys.len()];
-    for _ in 0..20 {
-        for (i, monkey) in monkeys.iter().enumerate() {
-            let items = std::mem::take(&mut entries_per_monkey[i]);
-            inspection_counts[i] += items.len();
-            for item in items {
-                let item = apply_op(monkey.op, item) / 3;
-                let next_monkey = if item % monkey.test_divisible == 0 {
-                    monkey.true_branch
-                } else {
-                    monkey.false_branch
-                };
-                entries_per_monkey[next_monkey].push(item);
+fn parse_input(s: &str) -> Input {
+    try_parse_input(s).unwrap_or_else(|e| panic!("{e}"))
+}
+
+fn try_parse_input(s: &str) -> Result<Input, ParseError> {
+    let end = s.lines().count() + 1;
+    let mut monkeys = vec![];
+    // Which monkey throws to which, and on which line, to check once they're
+    // all known.
+    let mut throws = vec![];
+    let mut lines = s
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line))
+        .peekable();
+    while lines.peek().is_some() {
+        let monkey = monkeys.len();
+        let at = |line| {
+            move |reason| ParseError {
+                monkey,
+                line,
+                reason,
+            }
+        };
+        // Reads the next line, which should start with `prefix`, returning
+        // its number and the rest of it.
+        let mut field = |prefix: &str| {
+            let (line, text) = lines.next().unwrap_or((end, ""));
+            match text.trim().strip_prefix(prefix) {
+                Some(rest) => Ok((line, rest.trim())),
+                None => Err(at(line)(format!("expected a line starting {prefix:?}"))),
             }
+        };
+        let (line, rest) = field("Monkey ")?;
+        let id: usize = parse_number(rest.trim_end_matches(':')).map_err(at(line))?;
+        if id != monkey {
+            return Err(at(line)(format!("expected monkey {monkey}, not {id}")));
         }
+        let (line, rest) = field("Starting items:")?;
+        let items = rest
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(parse_number)
+            .collect::<Result<_, _>>()
+            .map_err(at(line))?;
+        let (line, rest) = field("Operation: new =")?;
+        let op = rest.parse().map_err(at(line))?;
+        let (line, rest) = field("Test: divisible by")?;
+        let test_divisible = parse_number(rest).map_err(at(line))?;
+        if test_divisible == 0 {
+            return Err(at(line)("can't test divisibility by 0".to_string()));
+        }
+        let (line, rest) = field("If true: throw to monkey")?;
+        let true_branch = parse_number(rest).map_err(at(line))?;
+        throws.push((monkey, line, true_branch));
+        let (line, rest) = field("If false: throw to monkey")?;
+        let false_branch = parse_number(rest).map_err(at(line))?;
+        throws.push((monkey, line, false_branch));
+        monkeys.push(Monkey {
+            items,
+            op,
+            test_divisible,
+            true_branch,
+            false_branch,
+        });
+        lines.next_if(|(_, line)| line.trim().is_empty());
     }
-    inspection_counts.sort();
-    let n = inspection_counts.len();
-    inspection_counts[n - 1] * inspection_counts[n - 2]
+    for (monkey, line, target) in throws {
+        if target >= monkeys.len() {
+            return Err(ParseError {
+                monkey,
+                line,
+                reason: format!("there's no monkey {target} to throw to"),
+            });
+        }
+    }
+    Ok(monkeys)
 }
 
-fn part2(monkeys: &Input) -> usize {
-    let mut entries_per_monkey: Vec<_> =
-        monkeys.iter().map(|monkey| monkey.items.clone()).collect();
-    let base: Item = monkeys
+fn parse_number<T: FromStr>(s: &str) -> Result<T, String> {
+    s.parse().map_err(|_| format!("{s:?} isn't a number"))
+}
+
+/// What to do to a worry level after each inspection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Relief {
+    /// Divide by this, rounding down.
+    DivideBy(Item),
+    /// Keep only the remainder modulo the lcm of the monkeys' divisors,
+    /// which doesn't change where any item goes. Only possible when no
+    /// operation divides, and worry levels may go below zero.
+    Modulo,
+    None,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rules {
+    rounds: usize,
+    relief: Relief,
+    /// Monkey business is the product of this many of the highest
+    /// inspection counts.
+    top: usize,
+}
+
+const PART1: Rules = Rules {
+    rounds: 20,
+    relief: Relief::DivideBy(3),
+    top: 2,
+};
+
+const PART2: Rules = Rules {
+    rounds: 10000,
+    relief: Relief::Modulo,
+    top: 2,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+enum Error {
+    /// An operation or the relief overflowed, went negative or divided by
+    /// zero. Rounds count from 1 and monkeys from 0.
+    OutOfRange {
+        round: usize,
+        monkey: usize,
+    },
+    /// The monkey's operation divides, so modular relief is impossible.
+    NotPolynomial {
+        monkey: usize,
+    },
+    DivisorsTooLarge,
+    /// The product of the inspection counts overflows.
+    BusinessTooLarge,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::OutOfRange { round, monkey } => write!(
+                f,
+                "round {round}: monkey {monkey}'s worry level went out of range"
+            ),
+            Error::NotPolynomial { monkey } => write!(
+                f,
+                "monkey {monkey} divides, so worry levels can't be kept modulo anything"
+            ),
+            Error::DivisorsTooLarge => write!(f, "the lcm of the divisors overflows"),
+            Error::BusinessTooLarge => write!(f, "the monkey business overflows"),
+        }
+    }
+}
+
+/// How many items each monkey inspects under `rules`, holding worry levels
+/// as `W`s.
+fn inspection_counts<W: Worry>(monkeys: &Input, rules: &Rules) -> Result<Vec<usize>, Error> {
+    let mut entries_per_monkey: Vec<Vec<W>> = monkeys
         .iter()
-        .map(|monkey| monkey.test_divisible)
-        .product1()
-        .unwrap();
+        .map(|monkey| {
+            monkey
+                .items
+                .iter()
+                .map(|&item| W::from_item(item))
+                .collect()
+        })
+        .collect();
+    let base = match rules.relief {
+        Relief::Modulo => {
+            if let Some(monkey) = monkeys.iter().position(|monkey| !monkey.op.is_polynomial()) {
+                return Err(Error::NotPolynomial { monkey });
+            }
+            math::lcm_all(monkeys.iter().map(|monkey| monkey.test_divisible))
+                .ok_or(Error::DivisorsTooLarge)?
+        }
+        _ => 0,
+    };
     let mut inspection_counts = vec![0; monkeys.len()];
-    for _ in 0..10000 {
+    for round in 1..=rules.rounds {
         for (i, monkey) in monkeys.iter().enumerate() {
             let items = std::mem::take(&mut entries_per_monkey[i]);
             inspection_counts[i] += items.len();
             for item in items {
-                let item = apply_op(monkey.op, item) % base;
-                let next_monkey = if item % monkey.test_divisible == 0 {
+                let out_of_range = || Error::OutOfRange { round, monkey: i };
+                let item = match rules.relief {
+                    Relief::DivideBy(k) => monkey
+                        .op
+                        .eval(&item)
+                        .and_then(|item| item.apply(BinOp::Div, &W::from_item(k))),
+                    Relief::Modulo => Some(W::from_item(monkey.op.eval_mod(item.rem(base), base))),
+                    Relief::None => monkey.op.eval(&item),
+                }
+                .ok_or_else(out_of_range)?;
+                let next_monkey = if item.rem(monkey.test_divisible) == 0 {
                     monkey.true_branch
                 } else {
                     monkey.false_branch
@@ -153,9 +498,25 @@ fn part2(monkeys: &Input) -> usize {
             }
         }
     }
-    inspection_counts.sort();
-    let n = inspection_counts.len();
-    inspection_counts[n - 1] * inspection_counts[n - 2]
+    Ok(inspection_counts)
+}
+
+fn monkey_business<W: Worry>(monkeys: &Input, rules: &Rules) -> Result<usize, Error> {
+    let mut counts = inspection_counts::<W>(monkeys, rules)?;
+    counts.sort_by(|a, b| b.cmp(a));
+    counts
+        .iter()
+        .take(rules.top)
+        .try_fold(1usize, |product, &count| product.checked_mul(count))
+        .ok_or(Error::BusinessTooLarge)
+}
+
+fn part1(monkeys: &Input) -> usize {
+    monkey_business::<Item>(monkeys, &PART1).unwrap_or_else(|e| panic!("{e}"))
+}
+
+fn part2(monkeys: &Input) -> usize {
+    monkey_business::<Item>(monkeys, &PART2).unwrap_or_else(|e| panic!("{e}"))
 }
 
 #[cfg(test)]
@@ -202,6 +563,167 @@ Test: divisible by 17
         assert_eq!(part2(&input), 2713310158);
     }
 
+    #[test]
+    fn expressions() {
+        let eval = |s: &str, old: Item| s.parse::<Expr>().unwrap().eval(&old);
+        assert_eq!(eval("old + old * 3", 5), Some(20));
+        assert_eq!(eval("(old + old) * 3", 5), Some(30));
+        assert_eq!(eval("old - 2 - 1", 5), Some(2));
+        assert_eq!(eval("old / 2 * 2", 5), Some(4));
+        assert_eq!(eval("((old))", 5), Some(5));
+        assert_eq!(eval("old - 6", 5), None);
+        assert_eq!(eval("old / (old - 5)", 5), None);
+        assert_eq!(eval("old * old", u64::MAX), None);
+        let parse = |s: &str| s.parse::<Expr>();
+        assert_eq!(
+            parse("old +"),
+            Err("the expression ended early".to_string())
+        );
+        assert_eq!(
+            parse("(old"),
+            Err("expected ')' but the expression ended".to_string())
+        );
+        assert_eq!(
+            parse("old old"),
+            Err("unexpected old in expression".to_string())
+        );
+        assert_eq!(parse("new * 2"), Err("unknown variable new".to_string()));
+        assert_eq!(
+            parse("old % 2"),
+            Err("unexpected '%' in expression".to_string())
+        );
+    }
+
+    #[test]
+    fn modular_evaluation() {
+        for s in [
+            "old * old - 3 * old + 7",
+            "(old + 4) * (old - 1)",
+            "old * 19",
+        ] {
+            let expr: Expr = s.parse().unwrap();
+            assert!(expr.is_polynomial());
+            for old in 10..50 {
+                let m = 13 * 17;
+                assert_eq!(expr.eval_mod(old % m, m), expr.eval(&old).unwrap() % m);
+            }
+        }
+        assert!(!"old * (old / 3)".parse::<Expr>().unwrap().is_polynomial());
+        // Below zero is out of range normally, but modular worry levels
+        // are signed.
+        let expr: Expr = "old - 10".parse().unwrap();
+        assert_eq!(expr.eval(&3), None);
+        assert_eq!(expr.eval_mod(3, 7), (3i64 - 10).rem_euclid(7) as Item);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |s: &str| try_parse_input(s).err().unwrap().to_string();
+        let broken = SAMPLE.replace("old * old", "old ** old");
+        assert_eq!(
+            error(&broken),
+            "monkey 2, line 17: unexpected '*' in expression"
+        );
+        let broken = SAMPLE.replace("divisible by 17", "divisible by seventeen");
+        assert_eq!(
+            error(&broken),
+            "monkey 3, line 25: \"seventeen\" isn't a number"
+        );
+        let broken = SAMPLE.replace("divisible by 17", "divisible by 0");
+        assert_eq!(
+            error(&broken),
+            "monkey 3, line 25: can't test divisibility by 0"
+        );
+        let broken = SAMPLE.replace("If false: throw to monkey 0", "If false: throw to monkey 4");
+        assert_eq!(
+            error(&broken),
+            "monkey 1, line 13: there's no monkey 4 to throw to"
+        );
+        let broken = SAMPLE.replace("Monkey 1:", "Monkey 5:");
+        assert_eq!(error(&broken), "monkey 1, line 8: expected monkey 1, not 5");
+        let truncated = &SAMPLE[..SAMPLE.find("    If false: throw to monkey 1").unwrap()];
+        assert_eq!(
+            error(truncated),
+            "monkey 3, line 27: expected a line starting \"If false: throw to monkey\""
+        );
+        // Operations beyond the puzzle's still work in both parts.
+        let input = parse_input(&SAMPLE.replace("old + 3", "(old + 3 * (old + 1)) - old * 3"));
+        assert_eq!(part1(&input), part1(&parse_input(SAMPLE)));
+        assert_eq!(part2(&input), part2(&parse_input(SAMPLE)));
+    }
+
+    #[test]
+    fn rules() {
+        let input = parse_input(SAMPLE);
+        let counts = |rounds, relief| {
+            inspection_counts::<Item>(
+                &input,
+                &Rules {
+                    rounds,
+                    relief,
+                    top: 2,
+                },
+            )
+        };
+        // The counts the puzzle gives for part 2.
+        assert_eq!(counts(1, Relief::Modulo), Ok(vec![2, 4, 3, 6]));
+        assert_eq!(counts(20, Relief::Modulo), Ok(vec![99, 97, 8, 103]));
+        assert_eq!(counts(20, Relief::DivideBy(3)), Ok(vec![101, 95, 7, 105]));
+        let business = |rules| monkey_business::<Item>(&input, &rules);
+        assert_eq!(business(Rules { top: 1, ..PART1 }), Ok(105));
+        assert_eq!(business(Rules { top: 4, ..PART1 }), Ok(105 * 101 * 95 * 7));
+        assert_eq!(
+            business(Rules {
+                rounds: 100_000,
+                top: 4,
+                ..PART2
+            }),
+            Err(Error::BusinessTooLarge)
+        );
+        // Squaring soon overflows without relief.
+        assert_eq!(
+            counts(20, Relief::None),
+            Err(Error::OutOfRange {
+                round: 13,
+                monkey: 0
+            })
+        );
+        let dividing = parse_input(&SAMPLE.replace("old + 6", "old / 2 + 6"));
+        assert_eq!(
+            inspection_counts::<Item>(&dividing, &PART2),
+            Err(Error::NotPolynomial { monkey: 1 })
+        );
+    }
+
+    #[test]
+    fn big_worry_levels_match_modular() {
+        let input = parse_input(SAMPLE);
+        for rounds in [1, 10, 15] {
+            assert_eq!(
+                inspection_counts::<BigUint>(
+                    &input,
+                    &Rules {
+                        rounds,
+                        relief: Relief::None,
+                        top: 2
+                    }
+                ),
+                inspection_counts::<Item>(
+                    &input,
+                    &Rules {
+                        rounds,
+                        relief: Relief::Modulo,
+                        top: 2
+                    }
+                ),
+            );
+        }
+        assert_eq!(
+            monkey_business::<BigUint>(&input, &PART1),
+            monkey_business::<Item>(&input, &PART1)
+        );
+    }
+
     #[test]
     fn part1_my_input() {
         let input = parse_input(MY_INPUT);
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::VecDeque;
 
 use aoc_2022::Grid;
 
@@ -6,8 +6,32 @@ const MY_INPUT: &str = include_str!("../../inputs/day-12.txt");
 
 fn main() {
     let input = parse_input(MY_INPUT);
-    println!("part1: {}", part1(&input));
-    println!("part2: {}", part2(&input));
+    let show = |steps: Option<usize>| steps.map_or("unreachable".to_string(), |n| n.to_string());
+    println!("part1: {}", show(part1(&input)));
+    println!("part2: {}", show(part2(&input)));
+    // `--climb <k>` lets each step climb up to k, and `--symmetric` limits
+    // drops the same way. `--route` draws the routes.
+    let args: Vec<String> = std::env::args().collect();
+    let k = args
+        .windows(2)
+        .find(|pair| pair[0] == "--climb")
+        .map_or(1, |pair| pair[1].parse().unwrap());
+    let climb = if args.iter().any(|arg| arg == "--symmetric") {
+        Climb::Symmetric(k)
+    } else {
+        Climb::UpTo(k)
+    };
+    if args.iter().any(|arg| arg == "--route") {
+        for route in [part1_route(&input, climb), part2_route(&input, climb)] {
+            match route {
+                Some(route) => {
+                    println!("\n{} steps:", route.len() - 1);
+                    print!("{}", render_route(&input.heights, &route));
+                }
+                None => println!("\nunreachable"),
+            }
+        }
+    }
 }
 
 struct Input {
@@ -39,60 +63,107 @@ fn parse_input(s: &str) -> Input {
     }
 }
 
-fn part1(input: &Input) -> i32 {
-    let mut frontier = vec![input.start];
-    let mut discovered: HashSet<(usize, usize)> = HashSet::new();
-    discovered.insert(input.start);
-    let mut distance = 1;
-    while !frontier.is_empty() {
-        let mut next_frontier = vec![];
-        for rc in frontier {
-            let height = input.heights[rc];
-            for n in input.heights.neighbors4(rc) {
-                let n_height = input.heights[n];
-                if n_height > height + 1 {
-                    continue;
-                }
-                if n == input.end {
-                    return distance;
-                }
-                if discovered.insert(n) {
-                    next_frontier.push(n);
-                }
-            }
+/// How far the route can climb or drop in one step.
+#[derive(Clone, Copy)]
+enum Climb {
+    /// Up at most this much, and down any amount.
+    UpTo(u8),
+    /// Up or down at most this much.
+    Symmetric(u8),
+}
+
+impl Climb {
+    fn allows(self, from: u8, to: u8) -> bool {
+        match self {
+            Climb::UpTo(k) => to <= from.saturating_add(k),
+            Climb::Symmetric(k) => from.abs_diff(to) <= k,
         }
-        distance += 1;
-        frontier = next_frontier;
     }
-    panic!("No path found");
 }
 
-fn part2(input: &Input) -> u16 {
-    let mut frontier = vec![input.end];
-    let mut discovered = HashSet::new();
-    discovered.insert(input.end);
-    let mut distance = 1;
-    while !frontier.is_empty() {
-        let mut new_frontier = vec![];
-        for rc in frontier {
-            let height = input.heights[rc];
-            for n in input.heights.neighbors4(rc) {
-                let n_height = input.heights[n];
-                if height > n_height + 1 {
-                    continue;
-                }
-                if n_height == 0 {
-                    return distance;
-                }
-                if discovered.insert(n) {
-                    new_frontier.push(n);
-                }
+/// The shortest route to `end` from any of `starts`, including both ends, or
+/// `None` if it can't be reached.
+fn shortest_route(
+    heights: &Grid<u8>,
+    starts: impl IntoIterator<Item = (usize, usize)>,
+    end: (usize, usize),
+    climb: Climb,
+) -> Option<Vec<(usize, usize)>> {
+    // Where each square was first reached from, with starts reached from
+    // themselves.
+    let mut came_from: Grid<Option<(usize, usize)>> = heights.map(|_| None);
+    let mut frontier = VecDeque::new();
+    for start in starts {
+        came_from[start] = Some(start);
+        frontier.push_back(start);
+    }
+    while let Some(rc) = frontier.pop_front() {
+        if rc == end {
+            let mut route = vec![rc];
+            let mut curr = rc;
+            while let Some(prev) = came_from[curr].filter(|&prev| prev != curr) {
+                route.push(prev);
+                curr = prev;
             }
+            route.reverse();
+            return Some(route);
         }
-        frontier = new_frontier;
-        distance += 1;
+        for n in heights.neighbors4(rc) {
+            if came_from[n].is_none() && climb.allows(heights[rc], heights[n]) {
+                came_from[n] = Some(rc);
+                frontier.push_back(n);
+            }
+        }
+    }
+    None
+}
+
+/// Draws the route over the heightmap like the puzzle does, with an arrow on
+/// each square showing which way it goes next.
+fn render_route(heights: &Grid<u8>, route: &[(usize, usize)]) -> String {
+    let mut chars = heights.map(|_| '.');
+    for pair in route.windows(2) {
+        let ((r, c), next) = (pair[0], pair[1]);
+        chars[(r, c)] = if next == (r, c + 1) {
+            '>'
+        } else if next.1 + 1 == c {
+            '<'
+        } else if next == (r + 1, c) {
+            'v'
+        } else {
+            '^'
+        };
+    }
+    if let Some(&end) = route.last() {
+        chars[end] = 'E';
+    }
+    let mut s = String::new();
+    for r in 0..chars.num_rows() {
+        s.extend(chars.row(r));
+        s.push('\n');
     }
-    panic!("No path found");
+    s
+}
+
+fn part1_route(input: &Input, climb: Climb) -> Option<Vec<(usize, usize)>> {
+    shortest_route(&input.heights, [input.start], input.end, climb)
+}
+
+fn part2_route(input: &Input, climb: Climb) -> Option<Vec<(usize, usize)>> {
+    let lowest = input
+        .heights
+        .enumerated_elems()
+        .filter(|&(_, &height)| height == 0)
+        .map(|(rc, _)| rc);
+    shortest_route(&input.heights, lowest, input.end, climb)
+}
+
+fn part1(input: &Input) -> Option<usize> {
+    part1_route(input, Climb::UpTo(1)).map(|route| route.len() - 1)
+}
+
+fn part2(input: &Input) -> Option<usize> {
+    part2_route(input, Climb::UpTo(1)).map(|route| route.len() - 1)
 }
 
 #[cfg(test)]
@@ -108,24 +179,61 @@ abdefghi";
     #[test]
     fn part1_sample() {
         let input = parse_input(SAMPLE);
-        assert_eq!(part1(&input), 31);
+        assert_eq!(part1(&input), Some(31));
     }
 
     #[test]
     fn part2_sample() {
         let input = parse_input(SAMPLE);
-        assert_eq!(part2(&input), 29);
+        assert_eq!(part2(&input), Some(29));
+    }
+
+    #[test]
+    fn render_sample() {
+        let input = parse_input(SAMPLE);
+        let route = part1_route(&input, Climb::UpTo(1)).unwrap();
+        assert_eq!((route[0], route[31]), (input.start, input.end));
+        // The puzzle draws a different route of the same length.
+        assert_eq!(
+            render_route(&input.heights, &route),
+            "v..v<<<<
+>v.vv<<^
+.v.v>E^^
+.>v>>>^^
+..>>>>>^
+"
+        );
+    }
+
+    #[test]
+    fn climb_rules() {
+        let input = parse_input(SAMPLE);
+        // Climbing any amount goes straight there.
+        assert_eq!(part1_route(&input, Climb::UpTo(25)).unwrap().len() - 1, 7);
+        assert_eq!(part2_route(&input, Climb::UpTo(25)).unwrap().len() - 1, 5);
+        // Not being able to drop more than one either way still works here.
+        assert_eq!(
+            part1_route(&input, Climb::Symmetric(1)).unwrap().len() - 1,
+            31
+        );
+        assert_eq!(part1_route(&input, Climb::UpTo(0)), None);
+        let walled = parse_input("SzE");
+        assert_eq!(part1(&walled), None);
+        assert_eq!(
+            part1_route(&walled, Climb::Symmetric(25)).unwrap(),
+            [(0, 0), (0, 1), (0, 2)]
+        );
     }
 
     #[test]
     fn part1_my_input() {
         let input = parse_input(MY_INPUT);
-        assert_eq!(part1(&input), 361);
+        assert_eq!(part1(&input), Some(361));
     }
 
     #[test]
     fn part2_my_input() {
         let input = parse_input(MY_INPUT);
-        assert_eq!(part2(&input), 354);
+        assert_eq!(part2(&input), Some(354));
     }
 }
@@ -1,6 +1,9 @@
 use std::cmp::Ordering;
+use std::fmt;
 use std::iter::Peekable;
-use std::str::{Chars, FromStr};
+use std::str::{CharIndices, FromStr};
+
+use itertools::Itertools;
 
 const MY_INPUT: &str = include_str!("../../inputs/day-13.txt");
 
@@ -8,60 +11,312 @@ fn main() {
     let input = parse_input(MY_INPUT);
     println!("part1: {}", part1(&input));
     println!("part2: {}", part2(&input));
+    if std::env::args().any(|arg| arg == "--explain") {
+        print!("\n{}", walkthrough(&input));
+    }
 }
 
 #[derive(Debug, Clone)]
 enum Packet {
-    Num(u8),
+    Num(Int),
     List(Vec<Packet>),
 }
 
+/// A non-negative integer of any size, as its decimal digits without leading
+/// zeros.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Int(String);
+
+impl Int {
+    fn new(digits: &str) -> Int {
+        let digits = digits.trim_start_matches('0');
+        Int(if digits.is_empty() { "0" } else { digits }.to_string())
+    }
+}
+
+impl Ord for Int {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Without leading zeros, longer numbers are bigger.
+        (self.0.len(), &self.0).cmp(&(other.0.len(), &other.0))
+    }
+}
+
+impl PartialOrd for Int {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Int {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Packet::Num(n) => write!(f, "{n}"),
+            Packet::List(list) => write!(f, "[{}]", list.iter().join(",")),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ParseError {
+    /// The text ended partway through a packet.
+    UnexpectedEnd,
+    /// A character that can't go here, with its byte offset.
+    Unexpected(char, usize),
+    /// More text after a complete packet, starting at this byte offset.
+    TrailingInput(usize),
+    /// A JSON value with no packet equivalent, like a string or a negative
+    /// number.
+    NotAPacket,
+}
+
 impl FromStr for Packet {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut cs = s.chars().peekable();
-        Ok(parse_packet(&mut cs))
+        let mut cs = s.char_indices().peekable();
+        let packet = parse_packet(&mut cs)?;
+        match cs.next() {
+            None => Ok(packet),
+            Some((i, _)) => Err(ParseError::TrailingInput(i)),
+        }
     }
 }
 
-fn parse_packet(cs: &mut Peekable<Chars>) -> Packet {
-    match cs.peek().unwrap() {
-        '[' => {
-            cs.next();
-            Packet::List(parse_list(cs))
+type Cursor<'a> = Peekable<CharIndices<'a>>;
+
+fn next(cs: &mut Cursor) -> Result<(usize, char), ParseError> {
+    cs.next().ok_or(ParseError::UnexpectedEnd)
+}
+
+fn parse_packet(cs: &mut Cursor) -> Result<Packet, ParseError> {
+    match next(cs)? {
+        (_, '[') => Ok(Packet::List(parse_list(cs)?)),
+        (_, n) if n.is_ascii_digit() => {
+            let mut digits = n.to_string();
+            while let Some(&(_, n)) = cs.peek().filter(|(_, n)| n.is_ascii_digit()) {
+                digits.push(n);
+                cs.next();
+            }
+            Ok(Packet::Num(Int::new(&digits)))
         }
-        n if n.is_ascii_digit() => Packet::Num(parse_number(cs)),
-        _ => panic!("malformed packet"),
+        (i, c) => Err(ParseError::Unexpected(c, i)),
     }
 }
 
-fn parse_number(cs: &mut Peekable<Chars>) -> u8 {
-    let mut val = cs.next().unwrap().to_digit(10).unwrap();
+fn parse_list(cs: &mut Cursor) -> Result<Vec<Packet>, ParseError> {
+    let mut list = vec![];
+    if let Some((_, ']')) = cs.peek() {
+        cs.next();
+        return Ok(list);
+    }
     loop {
-        match cs.peek() {
-            Some(n) if n.is_ascii_digit() => {
-                let n = cs.next().unwrap();
-                val = val * 10 + n.to_digit(10).unwrap();
+        list.push(parse_packet(cs)?);
+        match next(cs)? {
+            (_, ',') => continue,
+            (_, ']') => return Ok(list),
+            (i, c) => return Err(ParseError::Unexpected(c, i)),
+        }
+    }
+}
+
+/// A JSON value, which is what packets look like.
+#[derive(Debug, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    /// The number as written, so that big ones aren't rounded.
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl From<&Packet> for Json {
+    fn from(packet: &Packet) -> Json {
+        match packet {
+            Packet::Num(n) => Json::Number(n.0.clone()),
+            Packet::List(list) => Json::Array(list.iter().map(Json::from).collect()),
+        }
+    }
+}
+
+impl TryFrom<&Json> for Packet {
+    type Error = ParseError;
+
+    fn try_from(json: &Json) -> Result<Packet, ParseError> {
+        match json {
+            Json::Number(n) if !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()) => {
+                Ok(Packet::Num(Int::new(n)))
             }
-            _ => return val as u8,
+            Json::Array(values) => Ok(Packet::List(
+                values
+                    .iter()
+                    .map(Packet::try_from)
+                    .collect::<Result<_, _>>()?,
+            )),
+            _ => Err(ParseError::NotAPacket),
         }
     }
 }
 
-fn parse_list(cs: &mut Peekable<Chars>) -> Vec<Packet> {
-    let mut list = vec![];
-    if let Some(']') = cs.peek() {
-        cs.next();
-        return list;
+impl FromStr for Json {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cs = s.char_indices().peekable();
+        let json = parse_json(&mut cs)?;
+        skip_whitespace(&mut cs);
+        match cs.next() {
+            None => Ok(json),
+            Some((i, _)) => Err(ParseError::TrailingInput(i)),
+        }
     }
+}
+
+fn skip_whitespace(cs: &mut Cursor) {
+    while cs.next_if(|(_, c)| c.is_ascii_whitespace()).is_some() {}
+}
+
+fn expect(cs: &mut Cursor, expected: char) -> Result<(), ParseError> {
+    match next(cs)? {
+        (_, c) if c == expected => Ok(()),
+        (i, c) => Err(ParseError::Unexpected(c, i)),
+    }
+}
+
+fn parse_json(cs: &mut Cursor) -> Result<Json, ParseError> {
+    skip_whitespace(cs);
+    let &(i, c) = cs.peek().ok_or(ParseError::UnexpectedEnd)?;
+    let json = match c {
+        '[' | '{' => {
+            cs.next();
+            let close = if c == '[' { ']' } else { '}' };
+            let mut values = vec![];
+            skip_whitespace(cs);
+            if cs.next_if(|&(_, c)| c == close).is_none() {
+                loop {
+                    if c == '{' {
+                        skip_whitespace(cs);
+                        expect(cs, '"')?;
+                        let key = parse_json_string(cs)?;
+                        skip_whitespace(cs);
+                        expect(cs, ':')?;
+                        values.push((key, parse_json(cs)?));
+                    } else {
+                        values.push((String::new(), parse_json(cs)?));
+                    }
+                    skip_whitespace(cs);
+                    match next(cs)? {
+                        (_, ',') => continue,
+                        (_, c) if c == close => break,
+                        (i, c) => return Err(ParseError::Unexpected(c, i)),
+                    }
+                }
+            }
+            if c == '[' {
+                Json::Array(values.into_iter().map(|(_, v)| v).collect())
+            } else {
+                Json::Object(values)
+            }
+        }
+        '"' => {
+            cs.next();
+            Json::String(parse_json_string(cs)?)
+        }
+        '-' | '0'..='9' => {
+            let mut number = String::new();
+            while let Some((_, c)) = cs.next_if(|(_, c)| "+-.eE".contains(*c) || c.is_ascii_digit())
+            {
+                number.push(c);
+            }
+            if number.parse::<f64>().is_err() {
+                return Err(ParseError::Unexpected(c, i));
+            }
+            Json::Number(number)
+        }
+        _ => {
+            let word: String = std::iter::from_fn(|| cs.next_if(|(_, c)| c.is_ascii_alphabetic()))
+                .map(|(_, c)| c)
+                .collect();
+            match word.as_str() {
+                "null" => Json::Null,
+                "true" => Json::Bool(true),
+                "false" => Json::Bool(false),
+                _ => return Err(ParseError::Unexpected(c, i)),
+            }
+        }
+    };
+    Ok(json)
+}
+
+// Parses the rest of a string after its opening quote.
+fn parse_json_string(cs: &mut Cursor) -> Result<String, ParseError> {
+    let mut s = String::new();
     loop {
-        let packet = parse_packet(cs);
-        list.push(packet);
-        match cs.next().unwrap() {
-            ',' => continue,
-            ']' => return list,
-            _ => panic!("malformed packet"),
+        match next(cs)? {
+            (_, '"') => return Ok(s),
+            (_, '\\') => s.push(match next(cs)? {
+                (_, 'n') => '\n',
+                (_, 't') => '\t',
+                (_, 'r') => '\r',
+                (_, 'b') => '\u{8}',
+                (_, 'f') => '\u{c}',
+                (i, 'u') => {
+                    let hex: String = (0..4)
+                        .map(|_| next(cs).map(|(_, c)| c))
+                        .collect::<Result<_, _>>()?;
+                    u32::from_str_radix(&hex, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or(ParseError::Unexpected('u', i))?
+                }
+                (_, c @ ('"' | '\\' | '/')) => c,
+                (i, c) => return Err(ParseError::Unexpected(c, i)),
+            }),
+            (_, c) => s.push(c),
+        }
+    }
+}
+
+fn write_json_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    f.write_str("\"")
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{b}"),
+            Json::Number(n) => write!(f, "{n}"),
+            Json::String(s) => write_json_string(f, s),
+            Json::Array(values) => write!(f, "[{}]", values.iter().join(",")),
+            Json::Object(fields) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write_json_string(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                f.write_str("}")
+            }
         }
     }
 }
@@ -69,14 +324,17 @@ fn parse_list(cs: &mut Peekable<Chars>) -> Vec<Packet> {
 type Input = Vec<(Packet, Packet)>;
 
 fn parse_input(s: &str) -> Input {
-    s.split("\n\n")
-        .map(|pair| {
-            let (p1, p2) = pair.split_once('\n').unwrap();
-            let p1 = p1.parse().unwrap();
-            let p2 = p2.parse().unwrap();
-            (p1, p2)
-        })
-        .collect()
+    let packets: Vec<Packet> = s
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| line.parse().unwrap())
+        .collect();
+    assert!(
+        packets.len().is_multiple_of(2),
+        "packets should come in pairs"
+    );
+    packets.into_iter().tuples().collect()
 }
 
 impl PartialEq for Packet {
@@ -133,6 +391,160 @@ fn packet_order(left: &Packet, right: &Packet) -> Ordering {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Left,
+    Right,
+}
+
+impl Side {
+    fn name(self) -> &'static str {
+        match self {
+            Side::Left => "Left",
+            Side::Right => "Right",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Decision {
+    /// The first pair of integers to differ.
+    Integers(Int, Int),
+    /// One side's list had fewer items.
+    RanOut(Side),
+    /// Nothing differs.
+    Equal,
+}
+
+/// Why [`packet_order`] orders two packets the way it does.
+#[derive(Debug)]
+struct Explanation {
+    order: Ordering,
+    /// The index taken at each depth to get to what decided the order.
+    path: Vec<usize>,
+    /// Where an integer was wrapped in a list to compare it with one, and
+    /// which side it was on.
+    promotions: Vec<(Vec<usize>, Side)>,
+    decision: Decision,
+    // Each line of the puzzle's description, with its depth.
+    trace: Vec<(usize, String)>,
+}
+
+/// Compares two packets like [`packet_order`], keeping track of each step.
+fn explain(left: &Packet, right: &Packet) -> Explanation {
+    let mut explanation = Explanation {
+        order: Ordering::Equal,
+        path: vec![],
+        promotions: vec![],
+        decision: Decision::Equal,
+        trace: vec![],
+    };
+    if let Some(order) = explain_helper(left, right, 0, &mut vec![], &mut explanation) {
+        explanation.order = order;
+    }
+    explanation
+}
+
+// Returns the order once it's decided, or None to carry on comparing.
+fn explain_helper(
+    left: &Packet,
+    right: &Packet,
+    depth: usize,
+    path: &mut Vec<usize>,
+    e: &mut Explanation,
+) -> Option<Ordering> {
+    e.trace.push((depth, format!("Compare {left} vs {right}")));
+    let (order, decision) = match (left, right) {
+        (Packet::Num(l), Packet::Num(r)) => match l.cmp(r) {
+            Ordering::Equal => return None,
+            order => (order, Decision::Integers(l.clone(), r.clone())),
+        },
+        (Packet::List(l), Packet::List(r)) => {
+            let mut i = 0;
+            loop {
+                match (l.get(i), r.get(i)) {
+                    (None, None) => return None,
+                    (None, Some(_)) => break (Ordering::Less, Decision::RanOut(Side::Left)),
+                    (Some(_), None) => break (Ordering::Greater, Decision::RanOut(Side::Right)),
+                    (Some(l), Some(r)) => {
+                        path.push(i);
+                        let order = explain_helper(l, r, depth + 1, path, e);
+                        path.pop();
+                        if order.is_some() {
+                            return order;
+                        }
+                    }
+                }
+                i += 1;
+            }
+        }
+        (Packet::List(_), Packet::Num(_)) | (Packet::Num(_), Packet::List(_)) => {
+            let (side, left, right) = match (left, right) {
+                (Packet::Num(_), _) => {
+                    (Side::Left, Packet::List(vec![left.clone()]), right.clone())
+                }
+                _ => (Side::Right, left.clone(), Packet::List(vec![right.clone()])),
+            };
+            let promoted = if side == Side::Left { &left } else { &right };
+            e.trace.push((
+                depth + 1,
+                format!(
+                    "Mixed types; convert {} to {promoted} and retry comparison",
+                    side.name().to_lowercase()
+                ),
+            ));
+            e.promotions.push((path.clone(), side));
+            return explain_helper(&left, &right, depth + 1, path, e);
+        }
+    };
+    let verdict = match (&decision, left, right) {
+        (Decision::RanOut(side), Packet::List(l), Packet::List(r)) => {
+            // The path leads to where the shorter list ended.
+            path.push(l.len().min(r.len()));
+            format!("{} side ran out of items", side.name())
+        }
+        _ => {
+            let smaller = if order == Ordering::Less {
+                Side::Left
+            } else {
+                Side::Right
+            };
+            format!("{} side is smaller", smaller.name())
+        }
+    };
+    let right_order = if order == Ordering::Less {
+        "in"
+    } else {
+        "not in"
+    };
+    e.trace.push((
+        depth + 1,
+        format!("{verdict}, so inputs are {right_order} the right order"),
+    ));
+    e.path = path.clone();
+    e.order = order;
+    e.decision = decision;
+    Some(order)
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (depth, line) in &self.trace {
+            writeln!(f, "{}- {line}", "  ".repeat(*depth))?;
+        }
+        Ok(())
+    }
+}
+
+/// Explains every pair, like the puzzle's walkthrough of the sample.
+fn walkthrough(input: &Input) -> String {
+    input
+        .iter()
+        .enumerate()
+        .map(|(i, (left, right))| format!("== Pair {} ==\n{}", i + 1, explain(left, right)))
+        .join("\n")
+}
+
 fn part1(input: &Input) -> usize {
     input
         .iter()
@@ -193,10 +605,117 @@ mod tests {
 [1,[2,[3,[4,[5,6,7]]]],8,9]
 [1,[2,[3,[4,[5,6,0]]]],8,9]";
 
+    const WALKTHROUGH: &str = "== Pair 1 ==
+- Compare [1,1,3,1,1] vs [1,1,5,1,1]
+  - Compare 1 vs 1
+  - Compare 1 vs 1
+  - Compare 3 vs 5
+    - Left side is smaller, so inputs are in the right order
+
+== Pair 2 ==
+- Compare [[1],[2,3,4]] vs [[1],4]
+  - Compare [1] vs [1]
+    - Compare 1 vs 1
+  - Compare [2,3,4] vs 4
+    - Mixed types; convert right to [4] and retry comparison
+    - Compare [2,3,4] vs [4]
+      - Compare 2 vs 4
+        - Left side is smaller, so inputs are in the right order
+
+== Pair 3 ==
+- Compare [9] vs [[8,7,6]]
+  - Compare 9 vs [8,7,6]
+    - Mixed types; convert left to [9] and retry comparison
+    - Compare [9] vs [8,7,6]
+      - Compare 9 vs 8
+        - Right side is smaller, so inputs are not in the right order
+
+== Pair 4 ==
+- Compare [[4,4],4,4] vs [[4,4],4,4,4]
+  - Compare [4,4] vs [4,4]
+    - Compare 4 vs 4
+    - Compare 4 vs 4
+  - Compare 4 vs 4
+  - Compare 4 vs 4
+  - Left side ran out of items, so inputs are in the right order
+
+== Pair 5 ==
+- Compare [7,7,7,7] vs [7,7,7]
+  - Compare 7 vs 7
+  - Compare 7 vs 7
+  - Compare 7 vs 7
+  - Right side ran out of items, so inputs are not in the right order
+
+== Pair 6 ==
+- Compare [] vs [3]
+  - Left side ran out of items, so inputs are in the right order
+
+== Pair 7 ==
+- Compare [[[]]] vs [[]]
+  - Compare [[]] vs []
+    - Right side ran out of items, so inputs are not in the right order
+
+== Pair 8 ==
+- Compare [1,[2,[3,[4,[5,6,7]]]],8,9] vs [1,[2,[3,[4,[5,6,0]]]],8,9]
+  - Compare 1 vs 1
+  - Compare [2,[3,[4,[5,6,7]]]] vs [2,[3,[4,[5,6,0]]]]
+    - Compare 2 vs 2
+    - Compare [3,[4,[5,6,7]]] vs [3,[4,[5,6,0]]]
+      - Compare 3 vs 3
+      - Compare [4,[5,6,7]] vs [4,[5,6,0]]
+        - Compare 4 vs 4
+        - Compare [5,6,7] vs [5,6,0]
+          - Compare 5 vs 5
+          - Compare 6 vs 6
+          - Compare 7 vs 0
+            - Right side is smaller, so inputs are not in the right order
+";
+
+    #[test]
+    fn explain_sample() {
+        let input = parse_input(SAMPLE);
+        assert_eq!(walkthrough(&input), WALKTHROUGH);
+        for (left, right) in &input {
+            assert_eq!(explain(left, right).order, packet_order(left, right));
+        }
+        let int = |n: &str| Int::new(n);
+        let e = explain(&input[1].0, &input[1].1);
+        assert_eq!(e.path, [1, 0]);
+        assert_eq!(e.promotions, [(vec![1], Side::Right)]);
+        assert_eq!(e.decision, Decision::Integers(int("2"), int("4")));
+        let e = explain(&input[2].0, &input[2].1);
+        assert_eq!(e.order, Ordering::Greater);
+        assert_eq!(e.promotions, [(vec![0], Side::Left)]);
+        assert_eq!(e.decision, Decision::Integers(int("9"), int("8")));
+        let e = explain(&input[3].0, &input[3].1);
+        assert_eq!(
+            (e.path, e.decision),
+            (vec![3], Decision::RanOut(Side::Left))
+        );
+        let e = explain(&input[6].0, &input[6].1);
+        assert_eq!(
+            (e.path, e.decision),
+            (vec![0, 0], Decision::RanOut(Side::Right))
+        );
+        let e = explain(&input[7].0, &input[7].1);
+        assert_eq!(e.path, [1, 1, 1, 1, 2]);
+        // A promotion that doesn't decide anything is still reported.
+        let e = explain(&"[[1],2]".parse().unwrap(), &"[1,2]".parse().unwrap());
+        assert_eq!(e.order, Ordering::Equal);
+        assert_eq!(e.decision, Decision::Equal);
+        assert_eq!(e.promotions, [(vec![0], Side::Right)]);
+    }
+
     #[test]
     fn part1_sample() {
         let input = parse_input(SAMPLE);
         assert_eq!(part1(&input), 13);
+        // Files end with a newline, and may use CRLF.
+        let input = parse_input(&format!("{SAMPLE}\n"));
+        assert_eq!(part1(&input), 13);
+        let input = parse_input(&format!("{}\r\n", SAMPLE.replace('\n', "\r\n")));
+        assert_eq!(part1(&input), 13);
+        assert_eq!(parse_input("[1]\n[2]\n\n[3]\n[1]\n").len(), 2);
     }
 
     #[test]
@@ -205,6 +724,48 @@ mod tests {
         assert_eq!(part2(&input), 140);
     }
 
+    #[test]
+    fn round_trip_sample() {
+        for line in SAMPLE.lines().filter(|line| !line.is_empty()) {
+            let packet: Packet = line.parse().unwrap();
+            assert_eq!(packet.to_string(), line);
+            let json = Json::from(&packet);
+            assert_eq!(json.to_string(), line);
+            let from_json = Packet::try_from(&json).unwrap();
+            assert_eq!(from_json.to_string(), line);
+        }
+    }
+
+    #[test]
+    fn big_numbers() {
+        let packet = |s: &str| s.parse::<Packet>().unwrap();
+        assert!(packet("[256]") > packet("[255]"));
+        assert!(packet("[100000000000000000000]") > packet("[99999999999999999999]"));
+        assert!(packet("[0010]") == packet("[10]"));
+        assert_eq!(packet("[007,0]").to_string(), "[7,0]");
+    }
+
+    #[test]
+    fn parse_errors() {
+        let parse = |s: &str| s.parse::<Packet>().map(|_| ());
+        assert_eq!(parse(""), Err(ParseError::UnexpectedEnd));
+        assert_eq!(parse("[1,2"), Err(ParseError::UnexpectedEnd));
+        assert_eq!(parse("[1;2]"), Err(ParseError::Unexpected(';', 2)));
+        assert_eq!(parse("[1,,2]"), Err(ParseError::Unexpected(',', 3)));
+        assert_eq!(parse("[1]]"), Err(ParseError::TrailingInput(3)));
+        let json = Json::Array(vec![Json::Number("-1".to_string())]);
+        assert_eq!(
+            Packet::try_from(&json).map(|_| ()),
+            Err(ParseError::NotAPacket)
+        );
+        let json = Json::Object(vec![("a".to_string(), Json::Null)]);
+        assert_eq!(json.to_string(), r#"{"a":null}"#);
+        assert_eq!(
+            Packet::try_from(&json).map(|_| ()),
+            Err(ParseError::NotAPacket)
+        );
+    }
+
     #[test]
     fn part1_my_input() {
         let input = parse_input(MY_INPUT);
@@ -1,14 +1,45 @@
 use std::collections::HashSet;
 
+use aoc_2022::record::Recorder;
+
 const MY_INPUT: &str = include_str!("../../inputs/day-14.txt");
 
 fn main() {
     let input = parse_input(MY_INPUT);
-    println!("part1: {}", part1(&input));
+    let mut recorder = Recorder::from_args().with_color('o', [0xc2, 0xa0, 0x5a]);
+    println!("part1: {}", part1(&input, &mut recorder));
     println!("part2: {}", part2(&input));
+    recorder.finish().unwrap();
+    // `--source <x,y>`, which can be repeated, and `--floor <offset>` pour
+    // sand into the cave some other way, and `--show` draws where it ends up.
+    let args: Vec<String> = std::env::args().collect();
+    let sources: Vec<(i32, i32)> = args
+        .windows(2)
+        .filter(|pair| pair[0] == "--source")
+        .map(|pair| {
+            let (x, y) = pair[1].split_once(',').unwrap();
+            (x.parse().unwrap(), y.parse().unwrap())
+        })
+        .collect();
+    let floor = args
+        .windows(2)
+        .find(|pair| pair[0] == "--floor")
+        .map(|pair| pair[1].parse().unwrap());
+    if !sources.is_empty() || floor.is_some() {
+        let sources = if sources.is_empty() {
+            vec![SOURCE]
+        } else {
+            sources
+        };
+        let mut cave = Cave::new(&input, sources, floor);
+        println!("sand: {}", cave.fill());
+        if args.iter().any(|arg| arg == "--show") {
+            print!("{}", cave.render());
+        }
+    }
 }
 
-type Input = Vec<Vec<(u16, u16)>>;
+type Input = Vec<Vec<(i32, i32)>>;
 
 fn parse_input(s: &str) -> Input {
     s.lines()
@@ -23,7 +54,9 @@ fn parse_input(s: &str) -> Input {
         .collect()
 }
 
-fn build_walls(input: &Input) -> HashSet<(u16, u16)> {
+const SOURCE: (i32, i32) = (500, 0);
+
+fn build_walls(input: &Input) -> HashSet<(i32, i32)> {
     let mut walls = HashSet::new();
     for points in input {
         let (mut prev_x, mut prev_y) = points[0];
@@ -50,79 +83,145 @@ fn build_walls(input: &Input) -> HashSet<(u16, u16)> {
     walls
 }
 
-fn part1(input: &Input) -> i32 {
-    let mut filled = build_walls(input);
-    let floor = input
-        .iter()
-        .flat_map(|wall| wall.iter().map(|xy| xy.1))
-        .max()
-        .unwrap();
-    for count in 0.. {
-        let mut x = 500;
-        let mut y = 0;
-        loop {
-            if y >= floor {
-                return count;
-            }
-            if !filled.contains(&(x, y + 1)) {
-                y += 1;
-                continue;
-            }
-            if !filled.contains(&(x - 1, y + 1)) {
-                x -= 1;
-                y += 1;
-                continue;
-            }
-            if !filled.contains(&(x + 1, y + 1)) {
-                x += 1;
-                y += 1;
-                continue;
-            }
-            filled.insert((x, y));
-            break;
+#[derive(Debug, PartialEq)]
+enum Step {
+    /// A grain came to rest here.
+    Rested((i32, i32)),
+    /// A grain fell past the lowest wall, and will fall forever.
+    Abyss,
+    /// Every source is covered in sand.
+    Blocked,
+}
+
+/// Sand pouring into a cave, one grain at a time from each source in turn.
+struct Cave {
+    walls: HashSet<(i32, i32)>,
+    sand: HashSet<(i32, i32)>,
+    sources: Vec<(i32, i32)>,
+    // The way the last grain from each source fell. The next one follows
+    // the same way until the last free spot on it, so it carries on from
+    // there rather than from the source.
+    paths: Vec<Vec<(i32, i32)>>,
+    next_source: usize,
+    lowest_wall: i32,
+    floor: Option<i32>,
+}
+
+impl Cave {
+    /// A cave with a floor `floor` below its lowest wall, if given.
+    fn new(input: &Input, sources: Vec<(i32, i32)>, floor: Option<i32>) -> Cave {
+        let walls = build_walls(input);
+        let lowest_wall = walls.iter().map(|xy| xy.1).max().unwrap();
+        Cave {
+            walls,
+            sand: HashSet::new(),
+            paths: sources.iter().map(|&source| vec![source]).collect(),
+            sources,
+            next_source: 0,
+            lowest_wall,
+            floor: floor.map(|offset| lowest_wall + offset),
         }
     }
-    panic!("unreachable")
-}
 
-fn part2(input: &Input) -> i32 {
-    let mut filled = build_walls(input);
-    let floor = input
-        .iter()
-        .flat_map(|wall| wall.iter().map(|xy| xy.1))
-        .max()
-        .unwrap()
-        + 2;
-    for count in 1.. {
-        let mut x = 500;
-        let mut y = 0;
-        loop {
-            if y + 1 == floor {
-                filled.insert((x, y));
-                break;
-            }
-            if !filled.contains(&(x, y + 1)) {
-                y += 1;
-                continue;
+    fn is_filled(&self, xy: (i32, i32)) -> bool {
+        self.walls.contains(&xy) || self.sand.contains(&xy) || Some(xy.1) == self.floor
+    }
+
+    /// Drops a grain from the next source that isn't covered.
+    fn step(&mut self) -> Step {
+        for _ in 0..self.sources.len() {
+            let i = self.next_source;
+            self.next_source = (i + 1) % self.sources.len();
+            if !self.sand.contains(&self.sources[i]) {
+                return self.drop_from(i);
             }
-            if !filled.contains(&(x - 1, y + 1)) {
-                x -= 1;
-                y += 1;
-                continue;
+        }
+        Step::Blocked
+    }
+
+    fn drop_from(&mut self, i: usize) -> Step {
+        let mut path = std::mem::take(&mut self.paths[i]);
+        // Grains from other sources may have landed on the path since.
+        while path.last().is_some_and(|&xy| self.is_filled(xy)) {
+            path.pop();
+        }
+        if path.is_empty() {
+            path.push(self.sources[i]);
+        }
+        let step = loop {
+            let (x, y) = *path.last().unwrap();
+            if self.floor.is_none() && y >= self.lowest_wall {
+                break Step::Abyss;
             }
-            if !filled.contains(&(x + 1, y + 1)) {
-                x += 1;
-                y += 1;
-                continue;
+            let below = [(x, y + 1), (x - 1, y + 1), (x + 1, y + 1)];
+            match below.into_iter().find(|&xy| !self.is_filled(xy)) {
+                Some(xy) => path.push(xy),
+                None => {
+                    path.pop();
+                    self.sand.insert((x, y));
+                    break Step::Rested((x, y));
+                }
             }
-            filled.insert((x, y));
-            if x == 500 && y == 0 {
-                return count;
+        };
+        self.paths[i] = path;
+        step
+    }
+
+    /// Pours sand until it falls into the abyss or covers every source, and
+    /// returns how many grains came to rest.
+    fn fill(&mut self) -> usize {
+        let mut count = 0;
+        while let Step::Rested(_) = self.step() {
+            count += 1;
+        }
+        count
+    }
+
+    /// Draws the cave like the puzzle does, just big enough to show every
+    /// wall, grain and source, and the floor beneath them.
+    fn render(&self) -> String {
+        let points = || self.walls.iter().chain(&self.sand).chain(&self.sources);
+        let mut min_x = points().map(|xy| xy.0).min().unwrap();
+        let mut max_x = points().map(|xy| xy.0).max().unwrap();
+        if self.floor.is_some() {
+            // The puzzle shows a little more of the floor either side.
+            min_x -= 2;
+            max_x += 2;
+        }
+        let min_y = points().map(|xy| xy.1).min().unwrap().min(0);
+        let max_y = points().map(|xy| xy.1).max().unwrap();
+        let max_y = self.floor.unwrap_or(max_y);
+        let mut s = String::new();
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                s.push(if self.sand.contains(&(x, y)) {
+                    'o'
+                } else if self.is_filled((x, y)) {
+                    '#'
+                } else if self.sources.contains(&(x, y)) {
+                    '+'
+                } else {
+                    '.'
+                });
             }
-            break;
+            s.push('\n');
         }
+        s
+    }
+}
+
+fn part1(input: &Input, recorder: &mut Recorder) -> usize {
+    let mut cave = Cave::new(input, vec![SOURCE], None);
+    let mut count = 0;
+    while let Step::Rested(_) = cave.step() {
+        count += 1;
+        recorder.push(|| cave.render());
     }
-    panic!("unreachable")
+    count
+}
+
+fn part2(input: &Input) -> usize {
+    Cave::new(input, vec![SOURCE], Some(2)).fill()
 }
 
 #[cfg(test)]
@@ -135,7 +234,7 @@ mod tests {
     #[test]
     fn part1_sample() {
         let input = parse_input(SAMPLE);
-        assert_eq!(part1(&input), 24);
+        assert_eq!(part1(&input, &mut Recorder::disabled()), 24);
     }
 
     #[test]
@@ -144,10 +243,90 @@ mod tests {
         assert_eq!(part2(&input), 93);
     }
 
+    #[test]
+    fn render_sample() {
+        let input = parse_input(SAMPLE);
+        let mut cave = Cave::new(&input, vec![SOURCE], None);
+        assert_eq!(
+            cave.render(),
+            "......+...
+..........
+..........
+..........
+....#...##
+....#...#.
+..###...#.
+........#.
+........#.
+#########.
+"
+        );
+        assert_eq!(cave.step(), Step::Rested((500, 8)));
+        assert_eq!(cave.fill(), 23);
+        assert_eq!(cave.step(), Step::Abyss);
+        assert_eq!(
+            cave.render(),
+            "......+...
+..........
+......o...
+.....ooo..
+....#ooo##
+...o#ooo#.
+..###ooo#.
+....oooo#.
+.o.ooooo#.
+#########.
+"
+        );
+        let mut cave = Cave::new(&input, vec![SOURCE], Some(2));
+        cave.fill();
+        assert_eq!(cave.step(), Step::Blocked);
+        assert_eq!(
+            cave.render(),
+            "............o............
+...........ooo...........
+..........ooooo..........
+.........ooooooo.........
+........oo#ooo##o........
+.......ooo#ooo#ooo.......
+......oo###ooo#oooo......
+.....oooo.oooo#ooooo.....
+....oooooooooo#oooooo....
+...ooo#########ooooooo...
+..ooooo.......ooooooooo..
+#########################
+"
+        );
+    }
+
+    #[test]
+    fn several_sources() {
+        let input = parse_input(SAMPLE);
+        let sources = vec![(500, 0), (497, 0), (503, 2)];
+        let mut cave = Cave::new(&input, sources.clone(), Some(2));
+        let count = cave.fill();
+        assert!(sources.iter().all(|source| cave.sand.contains(source)));
+        // Dropping every grain all the way from its source gives the same
+        // sand, even where one source's sand lands on another's path.
+        let mut naive = Cave::new(&input, sources, Some(2));
+        let mut naive_count = 0;
+        loop {
+            for path in &mut naive.paths {
+                path.truncate(1);
+            }
+            match naive.step() {
+                Step::Rested(_) => naive_count += 1,
+                _ => break,
+            }
+        }
+        assert_eq!(count, naive_count);
+        assert!(naive.sand == cave.sand);
+    }
+
     #[test]
     fn part1_my_input() {
         let input = parse_input(MY_INPUT);
-        assert_eq!(part1(&input), 808);
+        assert_eq!(part1(&input, &mut Recorder::disabled()), 808);
     }
 
     #[test]
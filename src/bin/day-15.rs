@@ -1,11 +1,62 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use aoc_2022::RangeSet;
+use itertools::Itertools;
+
 const MY_INPUT: &str = include_str!("../../inputs/day-15.txt");
 const TARGET_ROW: i32 = 2000000;
 const UPPER_BOUND: i32 = 4000000;
 
 fn main() {
-    let input = parse_input(MY_INPUT);
-    println!("part1: {}", part1(&input, TARGET_ROW));
-    println!("part2: {}", part2(&input, UPPER_BOUND));
+    // `--input <path>`, `--row <y>` and `--bound <n>` point this at another
+    // puzzle, such as the sample with row 10 and bound 20.
+    let args: Vec<String> = std::env::args().collect();
+    let arg = |name: &str| {
+        args.windows(2)
+            .find(|pair| pair[0] == name)
+            .map(|pair| pair[1].clone())
+    };
+    let input = match arg("--input") {
+        Some(path) => parse_input(&std::fs::read_to_string(path).unwrap()),
+        None => parse_input(MY_INPUT),
+    };
+    let row = arg("--row").map_or(TARGET_ROW, |row| row.parse().unwrap());
+    let bound = arg("--bound").map_or(UPPER_BOUND, |bound| bound.parse().unwrap());
+    println!("part1: {}", part1(&input, row));
+    match part2(&input, bound) {
+        Ok(freq) => println!("part2: {freq}"),
+        Err(e) => println!("part2: {e}"),
+    }
+    // `--render <path>` draws the area from `--viewport <x0,y0,x1,y1>`,
+    // by default 0 to the bound, as a `--width <pixels>` wide .ppm or .svg,
+    // and `--report` describes each sensor.
+    if let Some(path) = arg("--render") {
+        let (min, max) = match arg("--viewport") {
+            Some(viewport) => {
+                let (x0, y0, x1, y1) = viewport
+                    .split(',')
+                    .map(|n| n.parse().unwrap())
+                    .collect_tuple()
+                    .unwrap();
+                ((x0, y0), (x1, y1))
+            }
+            None => ((0, 0), (bound, bound)),
+        };
+        let width = arg("--width").map_or(800, |width| width.parse().unwrap());
+        let view = Viewport { min, max, width };
+        let w = BufWriter::new(File::create(&path).unwrap());
+        if path.ends_with(".svg") {
+            render_svg(&input, &view, w).unwrap();
+        } else if path.ends_with(".ppm") {
+            render_ppm(&input, &view, w).unwrap();
+        } else {
+            panic!("can only render .ppm or .svg files, not {path}");
+        }
+    }
+    if args.iter().any(|arg| arg == "--report") {
+        print!("{}", report(&input, bound));
+    }
 }
 
 type Input = Vec<((i32, i32), (i32, i32))>;
@@ -31,89 +82,318 @@ fn manhattan_distance((x1, y1): (i32, i32), (x2, y2): (i32, i32)) -> i32 {
     (x1 - x2).abs() + (y1 - y2).abs()
 }
 
-fn part1(input: &Input, target_row: i32) -> i32 {
-    // In the row where y=2000000, how many positions cannot contain a beacon?
-    let mut beacon_free: Vec<(i32, i32)> = vec![];
+// The x positions in row y that lie within range of some sensor.
+fn row_coverage(input: &Input, y: i32) -> RangeSet<i32> {
+    let mut beacon_free = RangeSet::new();
     for &(sensor, closest_beacon) in input {
-        let y_distance_to_target = (target_row - sensor.1).abs();
+        let y_distance_to_target = (y - sensor.1).abs();
         let distance_to_beacon = manhattan_distance(sensor, closest_beacon);
-        if distance_to_beacon <= y_distance_to_target {
+        if distance_to_beacon < y_distance_to_target {
             continue;
         }
         let a = distance_to_beacon - y_distance_to_target;
-        let free_range = (sensor.0 - a, sensor.0 + a);
-        beacon_free.push(free_range);
-    }
-    beacon_free.sort_by_key(|&(start, _)| start);
-    let mut total = 0;
-    let mut prev_end = i32::MIN;
-    for (start, end) in beacon_free {
-        if prev_end < start {
-            total += end - start;
-            prev_end = end;
-            continue;
-        }
-        if prev_end >= end {
-            continue;
-        }
-        total += end - prev_end;
-        prev_end = end;
+        beacon_free.insert(sensor.0 - a..=sensor.0 + a);
     }
-    total
+    beacon_free
 }
 
-fn part2(input: &Input, bound: i32) -> i64 {
-    for y in 0..=bound {
-        if let Some(x) = scan(input, bound, y) {
-            return tuning_freq(x, y);
-        }
+fn part1(input: &Input, target_row: i32) -> i32 {
+    // In the row where y=2000000, how many positions cannot contain a beacon?
+    let covered = row_coverage(input, target_row);
+    let beacons_in_row = input
+        .iter()
+        .filter_map(|&(_, (x, y))| (y == target_row).then_some(x))
+        .unique()
+        .count();
+    // A row of i32s always has few enough points to count.
+    covered.len().unwrap() as i32 - beacons_in_row as i32
+}
+
+/// The tuning frequency of the only point within the bound no sensor covers.
+/// Stops looking once it finds a second.
+fn part2(input: &Input, bound: i32) -> Result<i64, String> {
+    match uncovered_points_up_to(input, (0, 0), (bound, bound), 2)[..] {
+        [(x, y)] => Ok(tuning_freq(x, y)),
+        [] => Err("every point is covered, so there's nowhere for the beacon".to_string()),
+        _ => Err("there's more than one place the beacon could be".to_string()),
     }
-    panic!("o boy");
 }
 
 fn tuning_freq(x: i32, y: i32) -> i64 {
     x as i64 * 4000000 + y as i64
 }
 
-// returns Some(x), where 0 <= x <= bound if (x, y) could be a beacon, or else
-// None if no such x exists.
-fn scan(input: &Input, bound: i32, y: i32) -> Option<i32> {
-    let mut beacon_free: Vec<(i32, i32)> = vec![];
-    for &(sensor, closest_beacon) in input {
-        let y_distance_to_target = (y - sensor.1).abs();
-        let distance_to_beacon = manhattan_distance(sensor, closest_beacon);
-        if distance_to_beacon <= y_distance_to_target {
-            continue;
-        }
-        let a = distance_to_beacon - y_distance_to_target;
-        let free_range = (sensor.0 - a, sensor.0 + a);
-        beacon_free.push(free_range);
-    }
-    beacon_free.sort_by_key(|&(start, _)| start);
-    let mut prev_end = i32::MIN;
-    for (start, end) in beacon_free {
-        if prev_end < start - 1 {
-            if start > 0 && start <= bound {
-                return Some(start - 1);
+/// Each sensor's range as inclusive (u, v) bounds, where u = x + y and
+/// v = x - y.
+fn rotated_square(&(sensor, beacon): &((i32, i32), (i32, i32))) -> ((i64, i64), (i64, i64)) {
+    let r = manhattan_distance(sensor, beacon) as i64;
+    let u = sensor.0 as i64 + sensor.1 as i64;
+    let v = sensor.0 as i64 - sensor.1 as i64;
+    ((u - r, u + r), (v - r, v + r))
+}
+
+/// Up to `limit` of the points in the rectangle from `min` to `max` which no
+/// sensor covers, in no particular order.
+///
+/// Turned 45 degrees, with u = x + y and v = x - y, each sensor's range is a
+/// square. Cutting the plane along every square's edges leaves a grid of
+/// cells each either wholly covered or not, and only the uncovered cells
+/// need searching point by point.
+fn uncovered_points_up_to(
+    input: &Input,
+    min: (i32, i32),
+    max: (i32, i32),
+    limit: usize,
+) -> Vec<(i32, i32)> {
+    let (min_x, min_y) = (min.0 as i64, min.1 as i64);
+    let (max_x, max_y) = (max.0 as i64, max.1 as i64);
+    if min_x > max_x || min_y > max_y {
+        return vec![];
+    }
+    let squares: Vec<_> = input.iter().map(rotated_square).collect();
+    // Cell boundaries along each axis, starting with those of the rectangle.
+    let mut us = vec![min_x + min_y, max_x + max_y + 1];
+    let mut vs = vec![min_x - max_y, max_x - min_y + 1];
+    for &((u0, u1), (v0, v1)) in &squares {
+        us.extend([u0, u1 + 1]);
+        vs.extend([v0, v1 + 1]);
+    }
+    let clamp = |cuts: &mut Vec<i64>| {
+        let (lo, hi) = (cuts[0], cuts[1]);
+        cuts.retain(|&cut| (lo..=hi).contains(&cut));
+        cuts.sort();
+        cuts.dedup();
+    };
+    clamp(&mut us);
+    clamp(&mut vs);
+    let mut points = vec![];
+    'cells: for (&u0, &u1) in us.iter().tuple_windows() {
+        for (&v0, &v1) in vs.iter().tuple_windows() {
+            let (u1, v1) = (u1 - 1, v1 - 1);
+            let covered = squares.iter().any(|&((su0, su1), (sv0, sv1))| {
+                (su0..=su1).contains(&u0) && (sv0..=sv1).contains(&v0)
+            });
+            if covered {
+                continue;
             }
-            if start > bound {
-                break;
+            // Only the part of the cell inside the rectangle, where
+            // min_x <= (u + v) / 2 <= max_x and min_y <= (u - v) / 2 <= max_y.
+            let lo = u0.max(2 * min_x - v1).max(v0 + 2 * min_y);
+            let hi = u1.min(2 * max_x - v0).min(v1 + 2 * max_y);
+            for u in lo..=hi {
+                let mut v = v0.max(2 * min_x - u).max(u - 2 * max_y);
+                let last = v1.min(2 * max_x - u).min(u - 2 * min_y);
+                // u and v must both be odd or both even to map back to a point.
+                if (u - v) % 2 != 0 {
+                    v += 1;
+                }
+                while v <= last {
+                    if points.len() == limit {
+                        break 'cells;
+                    }
+                    points.push((((u + v) / 2) as i32, ((u - v) / 2) as i32));
+                    v += 2;
+                }
             }
-            prev_end = end;
-            continue;
         }
-        if prev_end >= end {
-            continue;
+    }
+    points
+}
+
+/// The number of points two sensors both cover.
+fn overlap(a: &((i32, i32), (i32, i32)), b: &((i32, i32), (i32, i32))) -> u64 {
+    let ((au0, au1), (av0, av1)) = rotated_square(a);
+    let ((bu0, bu1), (bv0, bv1)) = rotated_square(b);
+    let (u0, u1) = (au0.max(bu0), au1.min(bu1));
+    let (v0, v1) = (av0.max(bv0), av1.min(bv1));
+    if u0 > u1 || v0 > v1 {
+        return 0;
+    }
+    // Points are where u and v are both even or both odd.
+    let evens = |lo: i64, hi: i64| (hi.div_euclid(2) - (lo - 1).div_euclid(2)) as u64;
+    let (u_evens, v_evens) = (evens(u0, u1), evens(v0, v1));
+    let (u_odds, v_odds) = (
+        (u1 - u0 + 1) as u64 - u_evens,
+        (v1 - v0 + 1) as u64 - v_evens,
+    );
+    u_evens * v_evens + u_odds * v_odds
+}
+
+/// Describes each sensor's range and overlaps, numbering sensors from 1 in
+/// input order, and which sensors surround the gaps within `bound`.
+fn report(input: &Input, bound: i32) -> String {
+    let mut s = String::new();
+    for (i, a) in input.iter().enumerate() {
+        let ((sx, sy), (bx, by)) = *a;
+        let radius = manhattan_distance(a.0, a.1);
+        s += &format!(
+            "Sensor {} at x={sx}, y={sy} (beacon at x={bx}, y={by}): radius {radius}\n",
+            i + 1
+        );
+        for (j, b) in input.iter().enumerate() {
+            let shared = overlap(a, b);
+            if i != j && shared > 0 {
+                s += &format!("  overlaps sensor {} on {shared} positions\n", j + 1);
+            }
         }
-        prev_end = end;
     }
-    None
+    let gaps = uncovered_points_up_to(input, (0, 0), (bound, bound), 10);
+    if gaps.is_empty() {
+        s += "Nothing is uncovered.\n";
+    }
+    for (x, y) in gaps {
+        let bounding = input
+            .iter()
+            .positions(|&(sensor, beacon)| {
+                manhattan_distance(sensor, (x, y)) == manhattan_distance(sensor, beacon) + 1
+            })
+            .map(|i| (i + 1).to_string())
+            .join(", ");
+        s += &format!("The gap at x={x}, y={y} lies just outside sensors {bounding}\n");
+    }
+    s
+}
+
+/// A rectangle of the plane to draw, `width` pixels wide.
+struct Viewport {
+    min: (i32, i32),
+    max: (i32, i32),
+    width: usize,
+}
+
+impl Viewport {
+    /// Units per pixel.
+    fn scale(&self) -> f64 {
+        (self.max.0 - self.min.0 + 1) as f64 / self.width as f64
+    }
+
+    fn height(&self) -> usize {
+        (((self.max.1 - self.min.1 + 1) as f64 / self.scale()).ceil() as usize).max(1)
+    }
+
+    fn pixel(&self, (x, y): (i32, i32)) -> Option<(usize, usize)> {
+        if x < self.min.0 || x > self.max.0 || y < self.min.1 || y > self.max.1 {
+            return None;
+        }
+        let px = ((x - self.min.0) as f64 / self.scale()) as usize;
+        let py = ((y - self.min.1) as f64 / self.scale()) as usize;
+        Some((px.min(self.width - 1), py.min(self.height() - 1)))
+    }
+}
+
+const UNCOVERED: [u8; 3] = [255, 60, 60];
+const SENSOR: [u8; 3] = [255, 215, 0];
+const BEACON: [u8; 3] = [0, 200, 255];
+// Isolated uncovered points are too small to show up when scaled down, so
+// up to this many are marked individually.
+const MAX_MARKED: usize = 10_000;
+
+/// Draws each pixel darker or greener by how many sensors cover its middle,
+/// with sensors, beacons and uncovered points marked on top.
+fn render_ppm(input: &Input, view: &Viewport, mut w: impl Write) -> io::Result<()> {
+    let (width, height) = (view.width, view.height());
+    let scale = view.scale();
+    let mut rgb = vec![0; width * height * 3];
+    let mut paint = |(px, py): (usize, usize), color: [u8; 3]| {
+        let i = (py * width + px) * 3;
+        rgb[i..i + 3].copy_from_slice(&color);
+    };
+    for py in 0..height {
+        for px in 0..width {
+            let x = view.min.0 + ((px as f64 + 0.5) * scale) as i32;
+            let y = view.min.1 + ((py as f64 + 0.5) * scale) as i32;
+            let covering = input
+                .iter()
+                .filter(|&&(sensor, beacon)| {
+                    manhattan_distance(sensor, (x, y)) <= manhattan_distance(sensor, beacon)
+                })
+                .count();
+            let shade = (covering * 40).min(200) as u8;
+            paint((px, py), [15, 15 + shade, 35 + shade / 4]);
+        }
+    }
+    for point in uncovered_points_up_to(input, view.min, view.max, MAX_MARKED) {
+        paint(view.pixel(point).unwrap(), UNCOVERED);
+    }
+    for &(sensor, beacon) in input {
+        if let Some(pixel) = view.pixel(beacon) {
+            paint(pixel, BEACON);
+        }
+        if let Some(pixel) = view.pixel(sensor) {
+            paint(pixel, SENSOR);
+        }
+    }
+    write!(w, "P6\n{width} {height}\n255\n")?;
+    w.write_all(&rgb)?;
+    w.flush()
+}
+
+/// Draws each sensor's range as a translucent diamond, so overlaps show up
+/// darker, with sensors, beacons and uncovered points marked on top.
+fn render_svg(input: &Input, view: &Viewport, mut w: impl Write) -> io::Result<()> {
+    let (x0, y0) = view.min;
+    let (units_wide, units_high) = (view.max.0 - x0 + 1, view.max.1 - y0 + 1);
+    // Markers stay a few pixels across however far out the view is.
+    let marker = 3.0 * view.scale().max(1.0 / 3.0);
+    let hex = |[r, g, b]: [u8; 3]| format!("#{r:02x}{g:02x}{b:02x}");
+    writeln!(
+        w,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{x0} {y0} {units_wide} {units_high}">"#,
+        view.width,
+        view.height()
+    )?;
+    writeln!(
+        w,
+        r##"<rect x="{x0}" y="{y0}" width="{units_wide}" height="{units_high}" fill="#0f0f23"/>"##
+    )?;
+    // Each point is drawn as the unit square below and to the right of it.
+    for &(sensor, beacon) in input {
+        let r = manhattan_distance(sensor, beacon) as f64;
+        let (x, y) = (sensor.0 as f64 + 0.5, sensor.1 as f64 + 0.5);
+        writeln!(
+            w,
+            r##"<polygon points="{x},{} {},{y} {x},{} {},{y}" fill="#50dc64" fill-opacity="0.3"/>"##,
+            y - r - 0.5,
+            x + r + 0.5,
+            y + r + 0.5,
+            x - r - 0.5,
+        )?;
+    }
+    for point in uncovered_points_up_to(input, view.min, view.max, MAX_MARKED) {
+        writeln!(
+            w,
+            r#"<circle cx="{}" cy="{}" r="{marker}" fill="{}"/>"#,
+            point.0 as f64 + 0.5,
+            point.1 as f64 + 0.5,
+            hex(UNCOVERED)
+        )?;
+    }
+    for &(sensor, beacon) in input {
+        for ((x, y), color) in [(beacon, BEACON), (sensor, SENSOR)] {
+            writeln!(
+                w,
+                r#"<circle cx="{}" cy="{}" r="{marker}" fill="{}"/>"#,
+                x as f64 + 0.5,
+                y as f64 + 0.5,
+                hex(color)
+            )?;
+        }
+    }
+    writeln!(w, "</svg>")?;
+    w.flush()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Every uncovered point, ordered by y then x.
+    fn uncovered_points(input: &Input, min: (i32, i32), max: (i32, i32)) -> Vec<(i32, i32)> {
+        let mut points = uncovered_points_up_to(input, min, max, usize::MAX);
+        points.sort_by_key(|&(x, y)| (y, x));
+        points
+    }
+
     const SAMPLE: &str = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15
 Sensor at x=9, y=16: closest beacon is at x=10, y=16
 Sensor at x=13, y=2: closest beacon is at x=15, y=3
@@ -138,7 +418,98 @@ Sensor at x=20, y=1: closest beacon is at x=15, y=3";
     #[test]
     fn part2_sample() {
         let input = parse_input(SAMPLE);
-        assert_eq!(part2(&input, 20), 56000011);
+        assert_eq!(part2(&input, 20), Ok(56000011));
+        // The puzzle's real bound leaves far too many places to list.
+        assert_eq!(
+            part2(&input, UPPER_BOUND),
+            Err("there's more than one place the beacon could be".to_string())
+        );
+        assert_eq!(
+            part2(&input, 13),
+            Err("every point is covered, so there's nowhere for the beacon".to_string())
+        );
+    }
+
+    #[test]
+    fn uncovered_points_sample() {
+        let input = parse_input(SAMPLE);
+        assert_eq!(uncovered_points(&input, (0, 0), (20, 20)), [(14, 11)]);
+        assert_eq!(uncovered_points(&input, (14, 11), (14, 11)), [(14, 11)]);
+        assert_eq!(uncovered_points(&input, (0, 0), (13, 20)), []);
+        // Compare a wider area with scanning each row.
+        let (min, max) = ((-10, -10), (35, 30));
+        let mut expected = vec![];
+        for y in min.1..=max.1 {
+            for gap in row_coverage(&input, y).gaps(min.0..=max.0) {
+                expected.extend(gap.map(|x| (x, y)));
+            }
+        }
+        assert_eq!(uncovered_points(&input, min, max), expected);
+    }
+
+    #[test]
+    fn overlap_sample() {
+        let input = parse_input(SAMPLE);
+        // Ranges touching at one point, and one within another.
+        let a = ((0, 0), (1, 0));
+        assert_eq!(overlap(&a, &((2, 0), (3, 0))), 1);
+        assert_eq!(overlap(&a, &((3, 0), (4, 0))), 0);
+        assert_eq!(overlap(&((0, 0), (0, 2)), &a), 5);
+        // Compare each pair with counting the points they share.
+        for a in &input {
+            for b in &input {
+                let (ra, rb) = (manhattan_distance(a.0, a.1), manhattan_distance(b.0, b.1));
+                let mut shared = 0;
+                for x in a.0 .0 - ra..=a.0 .0 + ra {
+                    for y in a.0 .1 - ra..=a.0 .1 + ra {
+                        if manhattan_distance(a.0, (x, y)) <= ra
+                            && manhattan_distance(b.0, (x, y)) <= rb
+                        {
+                            shared += 1;
+                        }
+                    }
+                }
+                assert_eq!(overlap(a, b), shared);
+            }
+        }
+    }
+
+    #[test]
+    fn report_sample() {
+        let input = parse_input(SAMPLE);
+        let report = report(&input, 20);
+        assert!(report.starts_with(
+            "Sensor 1 at x=2, y=18 (beacon at x=-2, y=15): radius 7\n  overlaps sensor 5 on 6 positions\n"
+        ));
+        assert!(
+            report.ends_with("The gap at x=14, y=11 lies just outside sensors 4, 6, 7, 10, 12\n")
+        );
+    }
+
+    #[test]
+    fn render_sample() {
+        let input = parse_input(SAMPLE);
+        let view = Viewport {
+            min: (0, 0),
+            max: (20, 20),
+            width: 21,
+        };
+        let mut ppm = vec![];
+        render_ppm(&input, &view, &mut ppm).unwrap();
+        let header = b"P6\n21 21\n255\n";
+        assert_eq!(ppm.len(), header.len() + 21 * 21 * 3);
+        let pixel = |x: usize, y: usize| {
+            let i = header.len() + (y * 21 + x) * 3;
+            [ppm[i], ppm[i + 1], ppm[i + 2]]
+        };
+        assert_eq!(pixel(14, 11), UNCOVERED);
+        assert_eq!(pixel(2, 18), SENSOR);
+        assert_eq!(pixel(10, 16), BEACON);
+        let mut svg = vec![];
+        render_svg(&input, &view, &mut svg).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert_eq!(svg.matches("<polygon").count(), input.len());
+        assert!(svg.contains(r##"<circle cx="14.5" cy="11.5" r="3" fill="#ff3c3c"/>"##));
     }
 
     #[test]
@@ -150,6 +521,6 @@ Sensor at x=20, y=1: closest beacon is at x=15, y=3";
     #[test]
     fn part2_my_input() {
         let input = parse_input(MY_INPUT);
-        assert_eq!(part2(&input, UPPER_BOUND), 11840879211051);
+        assert_eq!(part2(&input, UPPER_BOUND), Ok(11840879211051));
     }
 }
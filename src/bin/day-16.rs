@@ -1,6 +1,9 @@
+use std::cmp::Reverse;
 use std::collections::HashMap;
 
-use aoc_2022::Grid;
+use aoc_2022::search::{self, BranchAndBound};
+use aoc_2022::{Grid, Interner, SymbolMap};
+use bit_set::BitSet;
 
 const MY_INPUT: &str = include_str!("../../inputs/day-16.txt");
 
@@ -8,33 +11,45 @@ fn main() {
     let input = parse_input(MY_INPUT);
     println!("part1: {}", part1(&input));
     println!("part2: {}", part2(&input));
+    if std::env::args().any(|arg| arg == "--plan") {
+        for time_budgets in [&[30][..], &[26, 26]] {
+            print!(
+                "\n{}",
+                best_team_plan(&input, time_budgets).narrative(&input)
+            );
+        }
+    }
 }
 
 struct Input {
-    start: u8,
+    start: usize,
     valves: Vec<Valve>,
 }
 
 struct Valve {
-    flow_rate: u8,
-    tunnels: Vec<u8>,
+    name: String,
+    flow_rate: u32,
+    tunnels: Vec<usize>,
 }
 
+/// A set of valves by number, as wide as the network needs.
+type ValveSet = BitSet;
+
 /// Returns a grid, g, where g[(src, dst)] is the min distance from node src to node dst.
-fn min_distances(input: &[Valve]) -> Grid<u8> {
+fn min_distances(input: &[Valve]) -> Grid<u32> {
     let min_distances = vec![vec![None; input.len()]; input.len()];
-    let mut min_distances: Grid<Option<u8>> = Grid::from(min_distances);
+    let mut min_distances: Grid<Option<u32>> = Grid::from(min_distances);
     for i in 0..input.len() {
         min_distances[(i, i)] = Some(0);
     }
-    let mut pairs_of_distance_i_minus_one: Vec<(u8, u8)> =
-        (0..input.len()).map(|i| (i as u8, i as u8)).collect();
+    let mut pairs_of_distance_i_minus_one: Vec<(usize, usize)> =
+        (0..input.len()).map(|i| (i, i)).collect();
     for i in 1..=input.len() {
         let mut pairs_of_distance_i = vec![];
         for (src, dst) in pairs_of_distance_i_minus_one {
-            for &n in &input[dst as usize].tunnels {
-                if min_distances[(src as usize, n as usize)].is_none() {
-                    min_distances[(src as usize, n as usize)] = Some(i as u8);
+            for &n in &input[dst].tunnels {
+                if min_distances[(src, n)].is_none() {
+                    min_distances[(src, n)] = Some(i as u32);
                     pairs_of_distance_i.push((src, n));
                 }
             }
@@ -45,181 +60,374 @@ fn min_distances(input: &[Valve]) -> Grid<u8> {
 }
 
 fn parse_input(s: &str) -> Input {
-    let parsed: HashMap<&str, (u8, Vec<&str>)> = s
-        .lines()
-        .map(|line| {
-            let words: Vec<_> = line.split_ascii_whitespace().collect();
-            let valve_name = words[1];
-            let flow_rate = words[4]; // rate=0;
-            let flow_rate = flow_rate
-                .split_once('=')
-                .unwrap()
-                .1
-                .trim_end_matches(';')
-                .parse()
-                .unwrap();
-            let tunnels = &words[9..];
-            let tunnels: Vec<_> = tunnels
-                .iter()
-                .map(|tunnel| tunnel.trim_end_matches(','))
-                .collect();
-            let valve = (flow_rate, tunnels);
-            (valve_name, valve)
+    // Valves are numbered in the order their names first appear.
+    let mut names = Interner::new();
+    let mut valves = SymbolMap::new();
+    for line in s.lines() {
+        let words: Vec<_> = line.split_ascii_whitespace().collect();
+        let valve_name = names.intern(words[1]);
+        let flow_rate = words[4]; // rate=0;
+        let flow_rate = flow_rate
+            .split_once('=')
+            .unwrap()
+            .1
+            .trim_end_matches(';')
+            .parse()
+            .unwrap();
+        let tunnels = &words[9..];
+        let tunnels: Vec<_> = tunnels
+            .iter()
+            .map(|tunnel| names.intern(tunnel.trim_end_matches(',')).index())
+            .collect();
+        valves.insert(valve_name, (flow_rate, tunnels));
+    }
+    let start = names.get("AA").unwrap();
+    let valves = names
+        .iter()
+        .map(|(id, name)| {
+            let (flow_rate, tunnels) = valves
+                .remove(id)
+                .unwrap_or_else(|| panic!("no description of valve {name}"));
+            Valve {
+                name: name.to_string(),
+                flow_rate,
+                tunnels,
+            }
         })
         .collect();
-    // normalize input to use integer ids rather than strings.
-    let mut name_to_id = HashMap::new();
-    let mut normalized = vec![];
-    let mut start = 0;
-    for (i, (&name, (flow_rate, _))) in parsed.iter().enumerate() {
-        name_to_id.insert(name, i as u8);
-        normalized.push(Valve {
-            flow_rate: *flow_rate,
-            tunnels: vec![],
-        });
-        if name == "AA" {
-            start = i;
-        }
-    }
-    for (&name, (_, tunnels)) in parsed.iter() {
-        let id = name_to_id[name];
-        let normalized_tunnels: Vec<_> = tunnels.iter().map(|&tunnel| name_to_id[tunnel]).collect();
-        normalized[id as usize].tunnels = normalized_tunnels;
-    }
     Input {
-        start: start as u8,
-        valves: normalized,
+        start: start.index(),
+        valves,
     }
 }
 
-fn part1(input: &Input) -> i32 {
-    let valves = &input.valves;
-    let mut max = 0;
-    let to_open: u64 = valves
+fn valves_to_open(valves: &[Valve]) -> ValveSet {
+    valves
         .iter()
         .enumerate()
         .filter(|(_, v)| v.flow_rate != 0)
-        .fold(0, |acc, (i, _)| acc | (1 << i));
+        .map(|(i, _)| i)
+        .collect()
+}
+
+fn part1(input: &Input) -> i32 {
+    let valves = &input.valves;
     let min_distances = min_distances(valves);
     find_most_pressure_to_release(
         valves,
         &min_distances,
         input.start,
-        0,
-        &mut max,
         30,
-        to_open,
-    );
-    max
+        valves_to_open(valves),
+    )
+}
+
+struct ValveSearch<'a> {
+    valves: &'a [Valve],
+    min_distances: &'a Grid<u32>,
+}
+
+#[derive(Clone)]
+struct SearchState {
+    curr_valve: usize,
+    time_left: u32,
+    to_open: ValveSet,
+    // Pressure released by the end, counting only the valves opened so far.
+    total_pressure: i32,
+}
+
+impl ValveSearch<'_> {
+    /// The time left after walking from `src` to `dst` and opening it, if
+    /// there's time to do that.
+    fn time_left_after_opening(&self, src: usize, dst: usize, time_left: u32) -> Option<u32> {
+        let d = self.min_distances[(src, dst)];
+        (d < time_left).then(|| time_left - d - 1)
+    }
+}
+
+impl BranchAndBound for ValveSearch<'_> {
+    type State = SearchState;
+    type Score = i32;
+    type Key = (usize, u32, ValveSet);
+
+    fn score(&self, state: &SearchState) -> i32 {
+        state.total_pressure
+    }
+
+    // As if every closed valve could be reached straight from the current one.
+    fn upper_bound(&self, state: &SearchState) -> i32 {
+        let extra: i32 = state
+            .to_open
+            .iter()
+            .filter_map(|n| {
+                let time_left =
+                    self.time_left_after_opening(state.curr_valve, n, state.time_left)?;
+                Some(time_left as i32 * self.valves[n].flow_rate as i32)
+            })
+            .sum();
+        state.total_pressure + extra
+    }
+
+    fn successors(&self, state: &SearchState) -> Vec<SearchState> {
+        state
+            .to_open
+            .iter()
+            .filter_map(|n| {
+                let time_left =
+                    self.time_left_after_opening(state.curr_valve, n, state.time_left)?;
+                let mut to_open = state.to_open.clone();
+                to_open.remove(n);
+                Some(SearchState {
+                    curr_valve: n,
+                    time_left,
+                    to_open,
+                    total_pressure: state.total_pressure
+                        + time_left as i32 * self.valves[n].flow_rate as i32,
+                })
+            })
+            .collect()
+    }
+
+    fn key(&self, state: &SearchState) -> Option<Self::Key> {
+        Some((state.curr_valve, state.time_left, state.to_open.clone()))
+    }
 }
 
-/// Populates max with the maximum possible amount of pressure which can
-/// be released from the given valves over the given amount of time.
+/// Returns the maximum possible amount of pressure which can be released
+/// from the given valves over the given amount of time.
 fn find_most_pressure_to_release(
     input: &[Valve],
-    min_distances: &Grid<u8>,
-    curr_valve: u8,
-    total_pressure: i32,
-    max: &mut i32,
-    time_left: u8,
-    to_open: u64,
-) {
-    for n in 0..input.len() {
-        if to_open & (1 << n) == 0 {
-            continue;
-        }
-        let d = min_distances[(curr_valve as usize, n as usize)];
-        if d >= time_left {
-            continue;
+    min_distances: &Grid<u32>,
+    start: usize,
+    time_left: u32,
+    to_open: ValveSet,
+) -> i32 {
+    let problem = ValveSearch {
+        valves: input,
+        min_distances,
+    };
+    let start = SearchState {
+        curr_valve: start,
+        time_left,
+        to_open,
+        total_pressure: 0,
+    };
+    search::maximize(&problem, start).score
+}
+
+/// The valves an agent opens, in order.
+type Route = Vec<usize>;
+
+/// For every set of valves one agent can open in `time_left` minutes, the
+/// most pressure it can release by opening exactly those and the route that
+/// does it, best first.
+fn best_per_subset(
+    input: &[Valve],
+    min_distances: &Grid<u32>,
+    start: usize,
+    time_left: u32,
+) -> Vec<(ValveSet, i32, Route)> {
+    let problem = ValveSearch {
+        valves: input,
+        min_distances,
+    };
+    let to_open = valves_to_open(input);
+    let mut best: HashMap<ValveSet, (i32, Route)> = HashMap::new();
+    let start = SearchState {
+        curr_valve: start,
+        time_left,
+        to_open: to_open.clone(),
+        total_pressure: 0,
+    };
+    let mut frontier = vec![(start, vec![])];
+    while let Some((state, route)) = frontier.pop() {
+        for next in problem.successors(&state) {
+            let mut route = route.clone();
+            route.push(next.curr_valve);
+            frontier.push((next, route));
         }
-        let new_time_left = time_left - d - 1;
-        let new_total_pressure =
-            total_pressure + (new_time_left as i32) * (input[n].flow_rate as i32);
-        if new_total_pressure > *max {
-            *max = new_total_pressure;
+        let opened: ValveSet = to_open.difference(&state.to_open).collect();
+        match best.get(&opened) {
+            Some(&(pressure, _)) if pressure >= state.total_pressure => {}
+            _ => {
+                best.insert(opened, (state.total_pressure, route));
+            }
         }
-        find_most_pressure_to_release(
-            input,
-            min_distances,
-            n as u8,
-            new_total_pressure,
-            max,
-            new_time_left,
-            to_open & !(1 << n),
-        );
     }
+    let mut best: Vec<_> = best
+        .into_iter()
+        .map(|(opened, (pressure, route))| (opened, pressure, route))
+        .collect();
+    best.sort_by_key(|&(_, pressure, _)| Reverse(pressure));
+    best
 }
 
-fn part2(input: &Input) -> i32 {
+/// How a team releases the most pressure: the route each agent takes, with
+/// the minutes it has to take it.
+struct Plan {
+    pressure: i32,
+    routes: Vec<(u32, Route)>,
+}
+
+/// The best plan for a team when each agent starts at the start valve with
+/// its own number of minutes.
+fn best_team_plan(input: &Input, time_budgets: &[u32]) -> Plan {
     let valves = &input.valves;
-    let mut max = 0;
-    let mut to_open = vec![];
     let min_distances = min_distances(valves);
-    for (id, valve) in valves.iter().enumerate() {
-        if valve.flow_rate != 0 {
-            to_open.push(id);
-        }
+    // Agents with the same amount of time share a table.
+    let mut tables = HashMap::new();
+    for &time_left in time_budgets {
+        tables
+            .entry(time_left)
+            .or_insert_with(|| best_per_subset(valves, &min_distances, input.start, time_left));
     }
-    // Try partitioning all the valves to open in every possible way, and assign one half of the partition
-    // to me and the other half of the partition to the elephant, and then solve those both independently.
-    with_all_partitions(&to_open, |assigned_to_me, assigned_to_eleph| {
-        let assigned_to_me = assigned_to_me.iter().fold(0, |acc, i| acc | (1 << i));
-        let assigned_to_eleph = assigned_to_eleph.iter().fold(0, |acc, i| acc | (1 << i));
-        let mut my_best = 0;
-        find_most_pressure_to_release(
-            valves,
-            &min_distances,
-            input.start,
-            0,
-            &mut my_best,
-            26,
-            assigned_to_me,
-        );
-        let mut elephant_best = 0;
-        find_most_pressure_to_release(
-            valves,
-            &min_distances,
-            input.start,
-            0,
-            &mut elephant_best,
-            26,
-            assigned_to_eleph,
-        );
-        let combined_best = my_best + elephant_best;
-        if combined_best > max {
-            max = combined_best;
+    // The best each set of valves can do when split among the agents so far,
+    // with their routes.
+    let mut team = vec![(ValveSet::new(), 0, vec![])];
+    for (i, time_left) in time_budgets.iter().enumerate() {
+        let table = &tables[time_left];
+        if i == time_budgets.len() - 1 {
+            // Both lists are best first, so stop once nothing can beat the best.
+            let mut best = None;
+            let mut max = 0;
+            for (t, (team_opened, team_pressure, _)) in team.iter().enumerate() {
+                if best.is_some() && team_pressure + table[0].1 <= max {
+                    break;
+                }
+                for (a, (opened, pressure, _)) in table.iter().enumerate() {
+                    if best.is_some() && team_pressure + pressure <= max {
+                        break;
+                    }
+                    if team_opened.is_disjoint(opened) {
+                        max = team_pressure + pressure;
+                        best = Some((t, a));
+                    }
+                }
+            }
+            let (t, a) = best.unwrap();
+            let mut routes = team.swap_remove(t).2;
+            routes.push(table[a].2.clone());
+            return Plan {
+                pressure: max,
+                routes: time_budgets.iter().copied().zip(routes).collect(),
+            };
+        }
+        let mut next: HashMap<ValveSet, (i32, Vec<Route>)> = HashMap::new();
+        for (team_opened, team_pressure, team_routes) in &team {
+            for (opened, pressure, route) in table {
+                if !team_opened.is_disjoint(opened) {
+                    continue;
+                }
+                let mut union = team_opened.clone();
+                union.union_with(opened);
+                let pressure = team_pressure + pressure;
+                if next.get(&union).is_none_or(|&(best, _)| best < pressure) {
+                    let mut routes = team_routes.clone();
+                    routes.push(route.clone());
+                    next.insert(union, (pressure, routes));
+                }
+            }
         }
-    });
-    max
+        team = next
+            .into_iter()
+            .map(|(opened, (pressure, routes))| (opened, pressure, routes))
+            .collect();
+        team.sort_by_key(|&(_, pressure, _)| Reverse(pressure));
+    }
+    Plan {
+        pressure: 0,
+        routes: vec![],
+    }
 }
 
-/// Invokes f with each possible partition of elems into 2 sets.
-fn with_all_partitions<T: Clone>(elems: &[T], mut f: impl FnMut(&[T], &[T])) {
-    let mut left = vec![];
-    let mut right = vec![];
-    partition_helper(elems, &mut left, &mut right, &mut f);
+enum Action {
+    Move(usize),
+    Open(usize),
 }
 
-fn partition_helper<T: Clone>(
-    elems: &[T],
-    left: &mut Vec<T>,
-    right: &mut Vec<T>,
-    f: &mut impl FnMut(&[T], &[T]),
-) {
-    match elems {
-        [] => f(left, right),
-        [elem, rest @ ..] => {
-            let elem = elem.clone();
-            left.push(elem);
-            partition_helper(rest, left, right, f);
-            let elem = left.pop().unwrap();
-            right.push(elem);
-            partition_helper(rest, left, right, f);
-            right.pop();
+impl Plan {
+    /// What each agent does each minute, following the shortest tunnels
+    /// between the valves on its route.
+    fn actions(&self, input: &Input) -> Vec<Vec<Option<Action>>> {
+        let min_distances = min_distances(&input.valves);
+        self.routes
+            .iter()
+            .map(|(time_budget, route)| {
+                let mut actions = vec![];
+                let mut curr = input.start;
+                for &valve in route {
+                    while curr != valve {
+                        curr = input.valves[curr]
+                            .tunnels
+                            .iter()
+                            .copied()
+                            .find(|&n| {
+                                min_distances[(n, valve)] + 1 == min_distances[(curr, valve)]
+                            })
+                            .unwrap();
+                        actions.push(Some(Action::Move(curr)));
+                    }
+                    actions.push(Some(Action::Open(valve)));
+                }
+                actions.resize_with(*time_budget as usize, || None);
+                actions
+            })
+            .collect()
+    }
+
+    /// Describes the plan minute by minute, like the puzzle does.
+    fn narrative(&self, input: &Input) -> String {
+        let actions = self.actions(input);
+        let minutes = actions.iter().map(|a| a.len()).max().unwrap_or(0);
+        let mut open: Vec<usize> = vec![];
+        let mut s = String::new();
+        for minute in 0..minutes {
+            if minute > 0 {
+                s.push('\n');
+            }
+            s += &format!("== Minute {} ==\n", minute + 1);
+            let mut names: Vec<_> = open.iter().map(|&v| &input.valves[v].name).collect();
+            names.sort();
+            let released: u32 = open.iter().map(|&v| input.valves[v].flow_rate).sum();
+            s += &match names.as_slice() {
+                [] => "No valves are open.".to_string(),
+                [name] => format!("Valve {name} is open, releasing {released} pressure."),
+                [a, b] => format!("Valves {a} and {b} are open, releasing {released} pressure."),
+                [init @ .., last] => {
+                    let init: Vec<_> = init.iter().map(|name| name.as_str()).collect();
+                    format!(
+                        "Valves {}, and {last} are open, releasing {released} pressure.",
+                        init.join(", ")
+                    )
+                }
+            };
+            s.push('\n');
+            for (agent, actions) in actions.iter().enumerate() {
+                let (who, s_) = match agent {
+                    0 => ("You".to_string(), ""),
+                    1 => ("The elephant".to_string(), "s"),
+                    _ => (format!("Elephant {agent}"), "s"),
+                };
+                match actions.get(minute) {
+                    Some(Some(Action::Move(v))) => {
+                        s += &format!("{who} move{s_} to valve {}.\n", input.valves[*v].name);
+                    }
+                    Some(Some(Action::Open(v))) => {
+                        s += &format!("{who} open{s_} valve {}.\n", input.valves[*v].name);
+                        open.push(*v);
+                    }
+                    _ => {}
+                }
+            }
         }
+        s
     }
 }
 
+fn part2(input: &Input) -> i32 {
+    best_team_plan(input, &[26, 26]).pressure
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -235,6 +443,148 @@ Valve HH has flow rate=22; tunnel leads to valve GG
 Valve II has flow rate=0; tunnels lead to valves AA, JJ
 Valve JJ has flow rate=21; tunnel leads to valve II";
 
+    // The puzzle's walkthrough of the best plan for part 1.
+    const WALKTHROUGH: &str = "== Minute 1 ==
+No valves are open.
+You move to valve DD.
+
+== Minute 2 ==
+No valves are open.
+You open valve DD.
+
+== Minute 3 ==
+Valve DD is open, releasing 20 pressure.
+You move to valve CC.
+
+== Minute 4 ==
+Valve DD is open, releasing 20 pressure.
+You move to valve BB.
+
+== Minute 5 ==
+Valve DD is open, releasing 20 pressure.
+You open valve BB.
+
+== Minute 6 ==
+Valves BB and DD are open, releasing 33 pressure.
+You move to valve AA.
+
+== Minute 7 ==
+Valves BB and DD are open, releasing 33 pressure.
+You move to valve II.
+
+== Minute 8 ==
+Valves BB and DD are open, releasing 33 pressure.
+You move to valve JJ.
+
+== Minute 9 ==
+Valves BB and DD are open, releasing 33 pressure.
+You open valve JJ.
+
+== Minute 10 ==
+Valves BB, DD, and JJ are open, releasing 54 pressure.
+You move to valve II.
+
+== Minute 11 ==
+Valves BB, DD, and JJ are open, releasing 54 pressure.
+You move to valve AA.
+
+== Minute 12 ==
+Valves BB, DD, and JJ are open, releasing 54 pressure.
+You move to valve DD.
+
+== Minute 13 ==
+Valves BB, DD, and JJ are open, releasing 54 pressure.
+You move to valve EE.
+
+== Minute 14 ==
+Valves BB, DD, and JJ are open, releasing 54 pressure.
+You move to valve FF.
+
+== Minute 15 ==
+Valves BB, DD, and JJ are open, releasing 54 pressure.
+You move to valve GG.
+
+== Minute 16 ==
+Valves BB, DD, and JJ are open, releasing 54 pressure.
+You move to valve HH.
+
+== Minute 17 ==
+Valves BB, DD, and JJ are open, releasing 54 pressure.
+You open valve HH.
+
+== Minute 18 ==
+Valves BB, DD, HH, and JJ are open, releasing 76 pressure.
+You move to valve GG.
+
+== Minute 19 ==
+Valves BB, DD, HH, and JJ are open, releasing 76 pressure.
+You move to valve FF.
+
+== Minute 20 ==
+Valves BB, DD, HH, and JJ are open, releasing 76 pressure.
+You move to valve EE.
+
+== Minute 21 ==
+Valves BB, DD, HH, and JJ are open, releasing 76 pressure.
+You open valve EE.
+
+== Minute 22 ==
+Valves BB, DD, EE, HH, and JJ are open, releasing 79 pressure.
+You move to valve DD.
+
+== Minute 23 ==
+Valves BB, DD, EE, HH, and JJ are open, releasing 79 pressure.
+You move to valve CC.
+
+== Minute 24 ==
+Valves BB, DD, EE, HH, and JJ are open, releasing 79 pressure.
+You open valve CC.
+
+== Minute 25 ==
+Valves BB, CC, DD, EE, HH, and JJ are open, releasing 81 pressure.
+
+== Minute 26 ==
+Valves BB, CC, DD, EE, HH, and JJ are open, releasing 81 pressure.
+
+== Minute 27 ==
+Valves BB, CC, DD, EE, HH, and JJ are open, releasing 81 pressure.
+
+== Minute 28 ==
+Valves BB, CC, DD, EE, HH, and JJ are open, releasing 81 pressure.
+
+== Minute 29 ==
+Valves BB, CC, DD, EE, HH, and JJ are open, releasing 81 pressure.
+
+== Minute 30 ==
+Valves BB, CC, DD, EE, HH, and JJ are open, releasing 81 pressure.
+";
+
+    #[test]
+    fn narrative_sample() {
+        let input = parse_input(SAMPLE);
+        let plan = best_team_plan(&input, &[30]);
+        assert_eq!(plan.pressure, 1651);
+        assert_eq!(plan.narrative(&input), WALKTHROUGH);
+        // Either agent could take either route.
+        let plan = best_team_plan(&input, &[26, 26]);
+        let mut routes: Vec<Vec<&str>> = plan
+            .routes
+            .iter()
+            .map(|(_, route)| {
+                route
+                    .iter()
+                    .map(|&v| input.valves[v].name.as_str())
+                    .collect()
+            })
+            .collect();
+        routes.sort();
+        assert_eq!(routes, [["DD", "HH", "EE"], ["JJ", "BB", "CC"]]);
+        let narrative = plan.narrative(&input);
+        assert!(narrative.ends_with(
+            "== Minute 26 ==\nValves BB, CC, DD, EE, HH, and JJ are open, releasing 81 pressure.\n"
+        ));
+    }
+
     #[test]
     fn part1_sample() {
         let input = parse_input(SAMPLE);
@@ -247,6 +597,54 @@ Valve JJ has flow rate=21; tunnel leads to valve II";
         assert_eq!(part2(&input), 1707);
     }
 
+    #[test]
+    fn teams_sample() {
+        let input = parse_input(SAMPLE);
+        assert_eq!(best_team_plan(&input, &[]).pressure, 0);
+        assert_eq!(best_team_plan(&input, &[30]).pressure, 1651);
+        assert_eq!(best_team_plan(&input, &[30, 0]).pressure, 1651);
+        assert_eq!(best_team_plan(&input, &[26, 26]).pressure, 1707);
+        // With an agent per working valve, each walks straight to its own.
+        assert_eq!(best_team_plan(&input, &[26; 6]).pressure, 1830);
+    }
+
+    #[test]
+    fn more_than_64_valves() {
+        // A corridor of 70 broken valves leads to one that works.
+        let mut s = String::from("Valve AA has flow rate=0; tunnels lead to valves X0\n");
+        for i in 0..70 {
+            let next = if i == 69 {
+                "ZZ".to_string()
+            } else {
+                format!("X{}", i + 1)
+            };
+            s += &format!("Valve X{i} has flow rate=0; tunnels lead to valves {next}\n");
+        }
+        s += "Valve ZZ has flow rate=10; tunnel leads to valve AA";
+        let input = parse_input(&s);
+        assert_eq!(input.valves.len(), 72);
+        assert_eq!(part1(&input), 0);
+        assert_eq!(best_team_plan(&input, &[80]).pressure, 80);
+        assert_eq!(best_team_plan(&input, &[80, 75]).pressure, 80);
+    }
+
+    #[test]
+    fn nothing_to_release() {
+        let broken = parse_input(&SAMPLE.replace(|c: char| c.is_ascii_digit(), "0"));
+        assert_eq!(best_team_plan(&broken, &[30]).pressure, 0);
+        assert_eq!(best_team_plan(&broken, &[26, 26]).pressure, 0);
+        // The only working valve is too far away to open in time.
+        let far = parse_input(
+            "Valve AA has flow rate=0; tunnels lead to valves BB
+Valve BB has flow rate=0; tunnels lead to valves AA, CC
+Valve CC has flow rate=0; tunnels lead to valves BB, DD
+Valve DD has flow rate=7; tunnel leads to valve CC",
+        );
+        assert_eq!(best_team_plan(&far, &[3]).pressure, 0);
+        assert_eq!(best_team_plan(&far, &[3, 2]).pressure, 0);
+        assert_eq!(best_team_plan(&far, &[3, 5]).pressure, 7);
+    }
+
     #[test]
     fn part1_my_input() {
         let input = parse_input(MY_INPUT);
@@ -1,11 +1,40 @@
+use aoc_2022::record::Recorder;
 use bit_set::BitSet;
 
 const MY_INPUT: &str = include_str!("../../inputs/day-17.txt");
 
 fn main() {
     let input = parse_input(MY_INPUT);
-    println!("part1: {}", part1(&input));
+    let mut recorder = Recorder::from_args();
+    // `--shapes <path>` reads rock diagrams from a file, `--width <n>` sets
+    // the chamber width, and `--watch <n>` draws every move of the first n
+    // rocks. Part 2 only knows the cycle for the puzzle's own rocks.
+    let args: Vec<String> = std::env::args().collect();
+    let arg = |name: &str| {
+        args.windows(2)
+            .find(|pair| pair[0] == name)
+            .map(|pair| pair[1].clone())
+    };
+    let shapes = match arg("--shapes") {
+        Some(path) => parse_shapes(&std::fs::read_to_string(&path).unwrap())
+            .unwrap_or_else(|e| panic!("{path}: {e}")),
+        None => parse_shapes(SHAPES).unwrap(),
+    };
+    let width = arg("--width").map_or(CHAMBER_WIDTH, |w| w.parse().unwrap());
+    if let Some(rocks) = arg("--watch") {
+        simulate(
+            &input,
+            &shapes,
+            width,
+            rocks.parse().unwrap(),
+            |pile, rock| {
+                println!("{}", pile.render(20, rock));
+            },
+        );
+    }
+    println!("part1: {}", part1(&input, &shapes, width, &mut recorder));
     println!("part2: {}", part2(&input));
+    recorder.finish().unwrap();
 }
 
 struct Input {
@@ -48,40 +77,87 @@ fn parse_input(s: &str) -> Input {
     Input { lefts: bs, len }
 }
 
-type Shape = [(u8, u8)];
-
-const SHAPES: &[&Shape] = &[
-    // ####
-    &[(0, 0), (0, 1), (0, 2), (0, 3)],
-    // .#.
-    // ###
-    // .#.
-    &[(0, 1), (1, 0), (1, 1), (1, 2), (2, 1)],
-    // ..#
-    // ..#
-    // ###
-    &[(0, 0), (0, 1), (0, 2), (1, 2), (2, 2)],
-    // #
-    // #
-    // #
-    // #
-    &[(0, 0), (1, 0), (2, 0), (3, 0)],
-    // ##
-    // ##
-    &[(0, 0), (0, 1), (1, 0), (1, 1)],
-];
-
-const CHAMBER_WIDTH: u8 = 7;
+/// A rock's cells as (row, column) offsets from its bottom left corner, with
+/// rows counting upwards.
+type Shape = Vec<(usize, usize)>;
+
+const SHAPES: &str = "####
+
+.#.
+###
+.#.
+
+..#
+..#
+###
+
+#
+#
+#
+#
+
+##
+##";
 
+const CHAMBER_WIDTH: usize = 7;
+
+/// Parses `#` diagrams of rocks separated by blank lines, skipping any
+/// without a `#`. It's an error for none of them to have one.
+fn parse_shapes(s: &str) -> Result<Vec<Shape>, String> {
+    let mut diagrams = vec![vec![]];
+    for line in s.lines() {
+        if line.trim().is_empty() {
+            diagrams.push(vec![]);
+        } else {
+            diagrams.last_mut().unwrap().push(line);
+        }
+    }
+    let shapes: Vec<Shape> = diagrams
+        .into_iter()
+        .filter_map(|rows| {
+            let mut shape = vec![];
+            for (i, row) in rows.iter().rev().enumerate() {
+                for (c, ch) in row.chars().enumerate() {
+                    if ch == '#' {
+                        shape.push((i, c));
+                    }
+                }
+            }
+            let min_r = shape.iter().map(|&(r, _)| r).min()?;
+            let min_c = shape.iter().map(|&(_, c)| c).min()?;
+            Some(shape.iter().map(|&(r, c)| (r - min_r, c - min_c)).collect())
+        })
+        .collect();
+    if shapes.is_empty() {
+        return Err("no rock diagram has a '#' in it".to_string());
+    }
+    Ok(shapes)
+}
+
+/// A rock on its way down, with its bottom left corner at `r`, `c`.
+struct Rock<'a> {
+    shape: &'a Shape,
+    r: usize,
+    c: usize,
+}
+
+impl Rock<'_> {
+    fn cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.shape
+            .iter()
+            .map(|&(sr, sc)| (self.r + sr, self.c + sc))
+    }
+}
+
+#[derive(Clone)]
 struct Pile {
-    layers: Vec<u8>,
+    layers: Vec<u64>,
+    width: usize,
 }
 
 impl Pile {
-    fn add_shape(&mut self, r: usize, c: u8, shape: &Shape) {
-        for &(sr, sc) in shape {
-            let r = r + sr as usize;
-            let c = c + sc;
+    fn add_rock(&mut self, rock: &Rock) {
+        for (r, c) in rock.cells() {
             while self.height() <= r {
                 self.layers.push(0);
             }
@@ -89,92 +165,149 @@ impl Pile {
         }
     }
 
-    fn can_move_left(&self, r: usize, c: u8, shape: &Shape) -> bool {
-        shape.iter().copied().all(|(sr, sc)| {
-            let r = r + sr as usize;
-            let c = c + sc;
-            c > 0 && (r >= self.height() || (self.layers[r] & (1 << (c - 1)) == 0))
-        })
+    fn is_free(&self, r: usize, c: usize) -> bool {
+        c < self.width && (r >= self.height() || self.layers[r] & (1 << c) == 0)
     }
 
-    fn can_move_right(&self, r: usize, c: u8, shape: &Shape) -> bool {
-        shape.iter().copied().all(|(sr, sc)| {
-            let r = r + sr as usize;
-            let c = c + sc;
-            (c + 1) < CHAMBER_WIDTH
-                && (r >= self.height() || (self.layers[r] & (1 << (c + 1)) == 0))
-        })
+    fn can_move_left(&self, rock: &Rock) -> bool {
+        rock.cells().all(|(r, c)| c > 0 && self.is_free(r, c - 1))
     }
 
-    fn cant_go_down(&self, r: usize, c: u8, shape: &Shape) -> bool {
-        if r == 0 {
-            return true;
-        }
-        for &(sr, sc) in shape {
-            let r = r + sr as usize;
-            let c = c + sc;
-            if r - 1 < self.height() && (self.layers[r - 1] & (1 << c) != 0) {
-                return true;
-            }
-        }
-        false
+    fn can_move_right(&self, rock: &Rock) -> bool {
+        rock.cells().all(|(r, c)| self.is_free(r, c + 1))
+    }
+
+    fn cant_go_down(&self, rock: &Rock) -> bool {
+        rock.cells().any(|(r, c)| r == 0 || !self.is_free(r - 1, c))
     }
 
-    fn new() -> Pile {
-        Pile { layers: vec![] }
+    fn new(width: usize) -> Pile {
+        assert!(width <= 64, "chambers can be at most 64 units wide");
+        Pile {
+            layers: vec![],
+            width,
+        }
     }
 
     fn height(&self) -> usize {
         self.layers.len()
     }
+
+    /// Draws the top `rows` rows of the chamber like the puzzle does,
+    /// including any falling rock and the floor once it's in view.
+    fn render(&self, rows: usize, rock: Option<&Rock>) -> String {
+        let falling: Vec<_> = rock.map(|rock| rock.cells().collect()).unwrap_or_default();
+        let top = falling
+            .iter()
+            .map(|&(r, _)| r + 1)
+            .max()
+            .unwrap_or(0)
+            .max(self.height());
+        let mut s = String::new();
+        for r in (top.saturating_sub(rows)..top).rev() {
+            s.push('|');
+            for c in 0..self.width {
+                s.push(if falling.contains(&(r, c)) {
+                    '@'
+                } else if !self.is_free(r, c) {
+                    '#'
+                } else {
+                    '.'
+                });
+            }
+            s.push_str("|\n");
+        }
+        if top <= rows {
+            s.push('+');
+            s.push_str(&"-".repeat(self.width));
+            s.push_str("+\n");
+        }
+        s
+    }
 }
 
-fn part1(input: &Input) -> usize {
+/// Drops `count` rocks, cycling through `shapes`, into a chamber `width`
+/// units wide.
+fn simulate(
+    input: &Input,
+    shapes: &[Shape],
+    width: usize,
+    count: usize,
+    mut watch: impl FnMut(&Pile, Option<&Rock>),
+) -> Pile {
     let mut jets = input.jet_iter();
-    let mut shapes = SHAPES.iter().copied().cycle();
-    let mut pile = Pile::new();
-    for _ in 0..2022 {
+    let mut shapes = shapes.iter().cycle();
+    let mut pile = Pile::new(width);
+    for _ in 0..count {
         let shape = shapes.next().unwrap();
-        insert_shape(shape, &mut jets, &mut pile);
+        insert_shape(shape, &mut jets, &mut pile, &mut watch);
     }
+    pile
+}
+
+fn part1(input: &Input, shapes: &[Shape], width: usize, recorder: &mut Recorder) -> usize {
+    let pile = simulate(input, shapes, width, 2022, |pile, rock| {
+        if rock.is_none() {
+            recorder.push(|| pile.render(40, None));
+        }
+    });
     pile.height()
 }
 
-fn insert_shape(shape: &Shape, jets: &mut impl Iterator<Item = Jet>, pile: &mut Pile) {
-    let mut shape_row = pile.height() + 3;
-    let mut shape_col: u8 = 2;
+/// Drops a rock into the pile, calling `watch` as it appears, after each
+/// move, and then once more with no rock when it comes to rest.
+fn insert_shape(
+    shape: &Shape,
+    jets: &mut impl Iterator<Item = Jet>,
+    pile: &mut Pile,
+    watch: &mut impl FnMut(&Pile, Option<&Rock>),
+) {
+    // Rocks start two units from the left wall, or as close as a narrow
+    // chamber allows.
+    let shape_width = shape.iter().map(|&(_, c)| c + 1).max().unwrap();
+    assert!(shape_width <= pile.width, "rock is wider than the chamber");
+    let mut rock = Rock {
+        shape,
+        r: pile.height() + 3,
+        c: 2.min(pile.width - shape_width),
+    };
+    watch(pile, Some(&rock));
     loop {
         match jets.next().unwrap() {
             Jet::Left => {
-                if pile.can_move_left(shape_row, shape_col, shape) {
-                    shape_col -= 1;
+                if pile.can_move_left(&rock) {
+                    rock.c -= 1;
                 }
             }
             Jet::Right => {
-                if pile.can_move_right(shape_row, shape_col, shape) {
-                    shape_col += 1;
+                if pile.can_move_right(&rock) {
+                    rock.c += 1;
                 }
             }
         }
-        if pile.cant_go_down(shape_row, shape_col, shape) {
-            pile.add_shape(shape_row, shape_col, shape);
+        watch(pile, Some(&rock));
+        if pile.cant_go_down(&rock) {
+            pile.add_rock(&rock);
+            watch(pile, None);
             break;
         }
-        shape_row -= 1;
+        rock.r -= 1;
+        watch(pile, Some(&rock));
     }
 }
 
 fn part2(input: &Input) -> usize {
     let mut jets = input.jet_iter();
-    let mut shapes = SHAPES.iter().copied().cycle();
-    let mut pile = Pile::new();
+    let shapes = parse_shapes(SHAPES).unwrap();
+    let mut shapes = shapes.iter().cycle();
+    let mut pile = Pile::new(CHAMBER_WIDTH);
     let mut i: u64 = 0;
     while i < 1000000000000 {
         if i == 5_953_690 {
             i = 999_987_726_090;
         }
         let shape = shapes.next().unwrap();
-        insert_shape(shape, &mut jets, &mut pile);
+        insert_shape(shape, &mut jets, &mut pile, &mut |_, _| ());
         i += 1;
     }
     pile.height() + 1_564_677_361_520
@@ -189,13 +322,78 @@ mod tests {
     #[test]
     fn part1_sample() {
         let input = parse_input(SAMPLE);
-        assert_eq!(part1(&input), 3068);
+        assert_eq!(
+            part1(
+                &input,
+                &parse_shapes(SHAPES).unwrap(),
+                CHAMBER_WIDTH,
+                &mut Recorder::disabled()
+            ),
+            3068
+        );
+    }
+
+    #[test]
+    fn watch_sample() {
+        let input = parse_input(SAMPLE);
+        let mut frames = vec![];
+        simulate(
+            &input,
+            &parse_shapes(SHAPES).unwrap(),
+            CHAMBER_WIDTH,
+            2,
+            |pile, rock| {
+                frames.push(pile.render(7, rock));
+            },
+        );
+        assert_eq!(
+            frames[0],
+            "|..@@@@.|\n|.......|\n|.......|\n|.......|\n+-------+\n"
+        );
+        assert_eq!(
+            frames[frames.len() - 2],
+            "|...@...|\n|..@@@..|\n|...@...|\n|..####.|\n+-------+\n"
+        );
+        assert_eq!(
+            frames[frames.len() - 1],
+            "|...#...|\n|..###..|\n|...#...|\n|..####.|\n+-------+\n"
+        );
+    }
+
+    #[test]
+    fn custom_shapes() {
+        let shapes = parse_shapes("##\n\n.#\n.#\n").unwrap();
+        assert_eq!(shapes, [vec![(0, 0), (0, 1)], vec![(0, 0), (1, 0)]]);
+        // Extra blank lines, CRLF line endings and diagrams with nothing in
+        // them are all fine.
+        assert_eq!(parse_shapes("##\n\n\n"), Ok(vec![vec![(0, 0), (0, 1)]]));
+        assert_eq!(
+            parse_shapes("\n\n##\n\n\n\n...\n\n.#\n.#\n\n"),
+            Ok(shapes.clone())
+        );
+        assert_eq!(parse_shapes("##\r\n\r\n.#\r\n.#\r\n"), Ok(shapes));
+        let none = Err("no rock diagram has a '#' in it".to_string());
+        assert_eq!(parse_shapes(""), none);
+        assert_eq!(parse_shapes("...\n\n.\n"), none);
+        // In a chamber one unit wide every rock lands straight on the last.
+        let input = parse_input(SAMPLE);
+        let shapes = parse_shapes("#\n#\n\n#").unwrap();
+        let pile = simulate(&input, &shapes, 1, 10, |_, _| ());
+        assert_eq!(pile.height(), 15);
     }
 
     #[test]
     fn part1_my_input() {
         let input = parse_input(MY_INPUT);
-        assert_eq!(part1(&input), 3137);
+        assert_eq!(
+            part1(
+                &input,
+                &parse_shapes(SHAPES).unwrap(),
+                CHAMBER_WIDTH,
+                &mut Recorder::disabled()
+            ),
+            3137
+        );
     }
 
     #[test]
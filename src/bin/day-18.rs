@@ -1,4 +1,6 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
 
 const MY_INPUT: &str = include_str!("../../inputs/day-18.txt");
 
@@ -6,9 +8,29 @@ fn main() {
     let input = parse_input(MY_INPUT);
     println!("part1: {}", part1(&input));
     println!("part2: {}", part2(&input));
+    // `--export <path>` writes the droplet's exterior as an OBJ or STL mesh.
+    // `--pockets` adds the air pockets inside it as separate objects, and
+    // `--binary` writes STL files in the binary format.
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(pair) = args.windows(2).find(|pair| pair[0] == "--export") {
+        let mesh = build_mesh(&input, args.iter().any(|arg| arg == "--pockets"));
+        let path = &pair[1];
+        let mut w = BufWriter::new(File::create(path).unwrap());
+        if path.ends_with(".obj") {
+            mesh.write_obj(&mut w).unwrap();
+        } else if path.ends_with(".stl") && args.iter().any(|arg| arg == "--binary") {
+            mesh.write_binary_stl(&mut w).unwrap();
+        } else if path.ends_with(".stl") {
+            mesh.write_ascii_stl(&mut w).unwrap();
+        } else {
+            panic!("can only export .obj or .stl files, not {path}");
+        }
+    }
 }
 
-type Input = Vec<(i8, i8, i8)>;
+type Point = (i32, i32, i32);
+
+type Input = Vec<Point>;
 
 fn parse_input(s: &str) -> Input {
     s.lines()
@@ -22,7 +44,7 @@ fn parse_input(s: &str) -> Input {
         .collect()
 }
 
-const NEIGHBOR_DELTAS: &[(i8, i8, i8)] = &[
+const NEIGHBOR_DELTAS: &[Point] = &[
     (-1, 0, 0),
     (1, 0, 0),
     (0, -1, 0),
@@ -31,54 +53,313 @@ const NEIGHBOR_DELTAS: &[(i8, i8, i8)] = &[
     (0, 0, 1),
 ];
 
+fn add((x, y, z): Point, (dx, dy, dz): Point) -> Point {
+    (x + dx, y + dy, z + dz)
+}
+
 fn part1(input: &Input) -> i32 {
     let mut surface_area = 0;
-    let mut grid: HashSet<(i8, i8, i8)> = HashSet::new();
-    for (x, y, z) in input.iter().copied() {
+    let mut grid: HashSet<Point> = HashSet::new();
+    for xyz in input.iter().copied() {
         let mut num_neighbors = 0;
-        for &(dx, dy, dz) in NEIGHBOR_DELTAS {
-            if grid.contains(&(x + dx, y + dy, z + dz)) {
+        for &delta in NEIGHBOR_DELTAS {
+            if grid.contains(&add(xyz, delta)) {
                 num_neighbors += 1;
             }
         }
         surface_area += 6 - 2 * num_neighbors;
-        grid.insert((x, y, z));
+        grid.insert(xyz);
     }
     surface_area
 }
 
-fn part2(input: &Input) -> i32 {
-    let min_x = input.iter().map(|xyz| xyz.0).min().unwrap() - 1;
-    let max_x = input.iter().map(|xyz| xyz.0).max().unwrap() + 1;
-    let min_y = input.iter().map(|xyz| xyz.1).min().unwrap() - 1;
-    let max_y = input.iter().map(|xyz| xyz.1).max().unwrap() + 1;
-    let min_z = input.iter().map(|xyz| xyz.2).min().unwrap() - 1;
-    let max_z = input.iter().map(|xyz| xyz.2).max().unwrap() + 1;
+/// The air cells touching the droplet, diagonally included, sorted into the
+/// outside and the pockets. Only those cells are looked at, so this takes as
+/// long as the droplet's surface rather than its volume.
+struct Air {
+    /// 0 for outside the droplet and 1 onwards for each pocket, in order of
+    /// their lowest cell.
+    regions: HashMap<Point, usize>,
+    pockets: usize,
+}
+
+impl Air {
+    fn around(lava: &HashSet<Point>) -> Air {
+        let mut cells: Vec<Point> = lava
+            .iter()
+            .flat_map(|&(x, y, z)| {
+                (-1..=1).flat_map(move |dx| {
+                    (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| (x + dx, y + dy, z + dz)))
+                })
+            })
+            .filter(|xyz| !lava.contains(xyz))
+            .collect();
+        cells.sort();
+        cells.dedup();
+        // Along any line, two of the cells with no lava between them are
+        // joined by air, and so is a cell with no lava beyond it to the
+        // outside. Straight runs like these also cross the open air away
+        // from the droplet, so no other cells need looking at.
+        let mut links = vec![vec![]; cells.len()];
+        let mut outside = vec![false; cells.len()];
+        for axis in 0..3 {
+            // Each line's cells and lava by position, with the cells' indices.
+            type Line = Vec<(i32, Option<usize>)>;
+            let mut lines: HashMap<(i32, i32), Line> = HashMap::new();
+            let along = |(x, y, z): Point| match axis {
+                0 => ((y, z), x),
+                1 => ((x, z), y),
+                _ => ((x, y), z),
+            };
+            for (i, &xyz) in cells.iter().enumerate() {
+                let (line, pos) = along(xyz);
+                lines.entry(line).or_default().push((pos, Some(i)));
+            }
+            for &xyz in lava {
+                let (line, pos) = along(xyz);
+                if let Some(line) = lines.get_mut(&line) {
+                    line.push((pos, None));
+                }
+            }
+            for line in lines.values_mut() {
+                line.sort();
+                for pair in line.windows(2) {
+                    if let [(_, Some(a)), (_, Some(b))] = *pair {
+                        links[a].push(b);
+                        links[b].push(a);
+                    }
+                }
+                for end in [line[0], line[line.len() - 1]] {
+                    if let (_, Some(i)) = end {
+                        outside[i] = true;
+                    }
+                }
+            }
+        }
+        fn fill(starts: Vec<usize>, r: usize, links: &[Vec<usize>], region: &mut [Option<usize>]) {
+            for &i in &starts {
+                region[i] = Some(r);
+            }
+            let mut frontier = starts;
+            while let Some(i) = frontier.pop() {
+                for &j in &links[i] {
+                    if region[j].is_none() {
+                        region[j] = Some(r);
+                        frontier.push(j);
+                    }
+                }
+            }
+        }
+        let mut region = vec![None; cells.len()];
+        let starts = (0..cells.len()).filter(|&i| outside[i]).collect();
+        fill(starts, 0, &links, &mut region);
+        let mut pockets = 0;
+        for i in 0..cells.len() {
+            if region[i].is_none() {
+                pockets += 1;
+                fill(vec![i], pockets, &links, &mut region);
+            }
+        }
+        Air {
+            regions: cells
+                .into_iter()
+                .zip(region.into_iter().flatten())
+                .collect(),
+            pockets,
+        }
+    }
+
+    /// The region of a cell next to the droplet, or `None` if it's lava.
+    fn region(&self, xyz: Point) -> Option<usize> {
+        self.regions.get(&xyz).copied()
+    }
+}
 
+fn part2(input: &Input) -> i32 {
     let lava: HashSet<_> = input.iter().copied().collect();
+    let air = Air::around(&lava);
+    lava.iter()
+        .flat_map(|&xyz| NEIGHBOR_DELTAS.iter().map(move |&delta| add(xyz, delta)))
+        .filter(|&xyz| air.region(xyz) == Some(0))
+        .count() as i32
+}
+
+/// A rectangle of surface, as its corners in anticlockwise order seen from
+/// the side it faces.
+type Quad = [Point; 4];
 
-    let mut sa = 0;
-    let air_corner = (min_x, min_y, min_z);
-    let mut frontier = vec![air_corner];
-    let mut discovered: HashSet<(i8, i8, i8)> = HashSet::new();
-    discovered.insert(air_corner);
-    while let Some(n) = frontier.pop() {
-        for &(dx, dy, dz) in NEIGHBOR_DELTAS {
-            let x = n.0 + dx;
-            let y = n.1 + dy;
-            let z = n.2 + dz;
-            if x < min_x || x > max_x || y < min_y || y > max_y || z < min_z || z > max_z {
+struct Mesh {
+    objects: Vec<(String, Vec<Quad>)>,
+}
+
+/// Covers the unit squares between the lava and the neighbouring cells
+/// `faces` picks, facing away from the lava, merging squares in the same
+/// plane into as few rectangles as it can.
+fn surface(lava: &HashSet<Point>, faces: impl Fn(Point) -> bool) -> Vec<Quad> {
+    // Squares facing along axis, towards + or -, in the plane at position
+    // along that axis, keyed by their lowest corner in the other two axes.
+    let mut planes: HashMap<(usize, bool, i32), HashSet<(i32, i32)>> = HashMap::new();
+    for &cell in lava {
+        let c = [cell.0, cell.1, cell.2];
+        for (axis, positive) in (0..3).flat_map(|axis| [(axis, false), (axis, true)]) {
+            let mut n = c;
+            n[axis] += if positive { 1 } else { -1 };
+            if !faces((n[0], n[1], n[2])) {
                 continue;
             }
-            let xyz = (x, y, z);
-            if lava.contains(&xyz) {
-                sa += 1;
-            } else if discovered.insert(xyz) {
-                frontier.push(xyz);
+            let position = c[axis] + positive as i32;
+            let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+            planes
+                .entry((axis, positive, position))
+                .or_default()
+                .insert((c[u], c[v]));
+        }
+    }
+    let mut quads = vec![];
+    for ((axis, positive, position), squares) in planes {
+        for ((u0, v0), (u1, v1)) in merge_squares(&squares) {
+            let corner = |u, v| {
+                let mut p = [0; 3];
+                p[axis] = position;
+                p[(axis + 1) % 3] = u;
+                p[(axis + 2) % 3] = v;
+                (p[0], p[1], p[2])
+            };
+            // The u and v axes follow on from axis, so going round u then v
+            // is anticlockwise seen from the positive side.
+            let mut quad = [
+                corner(u0, v0),
+                corner(u1, v0),
+                corner(u1, v1),
+                corner(u0, v1),
+            ];
+            if !positive {
+                quad.reverse();
             }
+            quads.push(quad);
         }
     }
-    sa
+    quads.sort();
+    quads
+}
+
+/// Greedily covers a set of unit squares with rectangles, given as their
+/// lowest and highest corners. Each starts from the lowest square left and
+/// is made as wide, then as tall, as it can be.
+fn merge_squares(squares: &HashSet<(i32, i32)>) -> Vec<((i32, i32), (i32, i32))> {
+    let mut left = squares.clone();
+    let mut sorted: Vec<_> = squares.iter().copied().collect();
+    sorted.sort_by_key(|&(u, v)| (v, u));
+    let mut rects = vec![];
+    for (u, v) in sorted {
+        if !left.contains(&(u, v)) {
+            continue;
+        }
+        let mut u1 = u + 1;
+        while left.contains(&(u1, v)) {
+            u1 += 1;
+        }
+        let mut v1 = v + 1;
+        while (u..u1).all(|u| left.contains(&(u, v1))) {
+            v1 += 1;
+        }
+        for v in v..v1 {
+            for u in u..u1 {
+                left.remove(&(u, v));
+            }
+        }
+        rects.push(((u, v), (u1, v1)));
+    }
+    rects
+}
+
+/// The outside of the droplet, and optionally the inside of each air pocket
+/// within it. Pockets' surfaces face into the pocket.
+fn build_mesh(input: &Input, pockets: bool) -> Mesh {
+    let lava: HashSet<_> = input.iter().copied().collect();
+    let air = Air::around(&lava);
+    let mut objects = vec![(
+        "droplet".to_string(),
+        surface(&lava, |xyz| air.region(xyz) == Some(0)),
+    )];
+    if pockets {
+        for pocket in 1..=air.pockets {
+            let name = format!("pocket{pocket}");
+            objects.push((name, surface(&lava, |xyz| air.region(xyz) == Some(pocket))));
+        }
+    }
+    Mesh { objects }
+}
+
+fn normal(quad: &Quad) -> Point {
+    let (a, b, c) = (quad[0], quad[1], quad[2]);
+    let (ux, uy, uz) = (b.0 - a.0, b.1 - a.1, b.2 - a.2);
+    let (vx, vy, vz) = (c.0 - b.0, c.1 - b.1, c.2 - b.2);
+    let n = (uy * vz - uz * vy, uz * vx - ux * vz, ux * vy - uy * vx);
+    (n.0.signum(), n.1.signum(), n.2.signum())
+}
+
+fn triangles(quad: &Quad) -> [[Point; 3]; 2] {
+    [[quad[0], quad[1], quad[2]], [quad[0], quad[2], quad[3]]]
+}
+
+impl Mesh {
+    fn write_obj(&self, mut w: impl Write) -> io::Result<()> {
+        let mut indices: HashMap<Point, usize> = HashMap::new();
+        for (name, quads) in &self.objects {
+            writeln!(w, "o {name}")?;
+            for quad in quads {
+                for p in quad {
+                    if !indices.contains_key(p) {
+                        indices.insert(*p, indices.len() + 1);
+                        writeln!(w, "v {} {} {}", p.0, p.1, p.2)?;
+                    }
+                }
+                let [a, b, c, d] = quad.map(|p| indices[&p]);
+                writeln!(w, "f {a} {b} {c} {d}")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_ascii_stl(&self, mut w: impl Write) -> io::Result<()> {
+        for (name, quads) in &self.objects {
+            writeln!(w, "solid {name}")?;
+            for quad in quads {
+                let n = normal(quad);
+                for triangle in triangles(quad) {
+                    writeln!(w, "  facet normal {} {} {}", n.0, n.1, n.2)?;
+                    writeln!(w, "    outer loop")?;
+                    for p in triangle {
+                        writeln!(w, "      vertex {} {} {}", p.0, p.1, p.2)?;
+                    }
+                    writeln!(w, "    endloop")?;
+                    writeln!(w, "  endfacet")?;
+                }
+            }
+            writeln!(w, "endsolid {name}")?;
+        }
+        Ok(())
+    }
+
+    /// Binary STL has no way to name objects, so they're all written as one.
+    fn write_binary_stl(&self, mut w: impl Write) -> io::Result<()> {
+        let quads: Vec<_> = self.objects.iter().flat_map(|(_, quads)| quads).collect();
+        w.write_all(&[0; 80])?;
+        w.write_all(&(2 * quads.len() as u32).to_le_bytes())?;
+        for quad in quads {
+            let n = normal(quad);
+            for triangle in triangles(quad) {
+                for p in [n].iter().chain(&triangle) {
+                    for x in [p.0, p.1, p.2] {
+                        w.write_all(&(x as f32).to_le_bytes())?;
+                    }
+                }
+                w.write_all(&[0; 2])?;
+            }
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -99,6 +380,19 @@ mod tests {
 2,1,5
 2,3,5";
 
+    fn area(quads: &[Quad]) -> i32 {
+        quads
+            .iter()
+            .map(|q| {
+                let (a, c) = (q[0], q[2]);
+                [(a.0 - c.0).abs(), (a.1 - c.1).abs(), (a.2 - c.2).abs()]
+                    .into_iter()
+                    .filter(|&d| d != 0)
+                    .product::<i32>()
+            })
+            .sum()
+    }
+
     #[test]
     fn part1_sample() {
         let input = parse_input(SAMPLE);
@@ -111,6 +405,112 @@ mod tests {
         assert_eq!(part2(&input), 58);
     }
 
+    #[test]
+    fn mesh_sample() {
+        let input = parse_input(SAMPLE);
+        let mesh = build_mesh(&input, true);
+        assert_eq!(2, mesh.objects.len());
+        assert_eq!(58, area(&mesh.objects[0].1));
+        // The pocket is a single cell.
+        assert_eq!(6, mesh.objects[1].1.len());
+        let mut stl = vec![];
+        mesh.write_binary_stl(&mut stl).unwrap();
+        let quads = mesh.objects[0].1.len() + 6;
+        assert_eq!(84 + 2 * quads * 50, stl.len());
+    }
+
+    #[test]
+    fn pocket_faces_inwards() {
+        // A 3x3x3 cube with its middle missing.
+        let mut input = vec![];
+        for x in 0..3 {
+            for y in 0..3 {
+                for z in 0..3 {
+                    if (x, y, z) != (1, 1, 1) {
+                        input.push((x, y, z));
+                    }
+                }
+            }
+        }
+        let mesh = build_mesh(&input, true);
+        let pocket = &mesh.objects[1].1;
+        assert_eq!(6, pocket.len());
+        for quad in pocket {
+            // Each normal points from its face towards the pocket's middle,
+            // at (1.5, 1.5, 1.5).
+            let (n, a) = (normal(quad), quad[0]);
+            let to_middle = (3 - 2 * a.0, 3 - 2 * a.1, 3 - 2 * a.2);
+            assert!(n.0 * to_middle.0 + n.1 * to_middle.1 + n.2 * to_middle.2 > 0);
+        }
+        let bottom = pocket.iter().find(|q| q.iter().all(|p| p.2 == 1)).unwrap();
+        assert_eq!((0, 0, 1), normal(bottom));
+    }
+
+    #[test]
+    fn merged_box() {
+        // A 3x2x1 box is 6 rectangles, with outward normals.
+        let input = parse_input("0,0,0\n1,0,0\n2,0,0\n0,1,0\n1,1,0\n2,1,0");
+        let mesh = build_mesh(&input, false);
+        let quads = &mesh.objects[0].1;
+        assert_eq!(6, quads.len());
+        assert_eq!(22, area(quads));
+        let top = quads.iter().find(|q| q.iter().all(|p| p.2 == 1)).unwrap();
+        assert_eq!((0, 0, 1), normal(top));
+        let mut obj = vec![];
+        mesh.write_obj(&mut obj).unwrap();
+        let obj = String::from_utf8(obj).unwrap();
+        assert_eq!(8, obj.lines().filter(|l| l.starts_with("v ")).count());
+        assert_eq!(6, obj.lines().filter(|l| l.starts_with("f ")).count());
+    }
+
+    #[test]
+    fn far_from_origin() {
+        let input = parse_input(SAMPLE);
+        for (dx, dy, dz) in [(1000, -2000, 30000), (-100_000, -7, 250)] {
+            let moved: Vec<_> = input
+                .iter()
+                .map(|&(x, y, z)| (x + dx, y + dy, z + dz))
+                .collect();
+            let text: Vec<_> = moved
+                .iter()
+                .map(|(x, y, z)| format!("{x},{y},{z}"))
+                .collect();
+            let moved = parse_input(&text.join("\n"));
+            assert_eq!(part1(&moved), 64);
+            assert_eq!(part2(&moved), 58);
+        }
+    }
+
+    #[test]
+    fn wide_droplet() {
+        // Three bars 1100 long from the origin, so the droplet's bounding
+        // box is far too big to fill, and a capped tube 600 long with a
+        // pocket running down the middle.
+        let mut input = vec![];
+        for i in 0..1100 {
+            input.extend([(i, 0, 0), (0, i + 1, 0), (0, 0, i + 1)]);
+        }
+        let len = 600;
+        for x in -1..=len {
+            for y in 10..13 {
+                for z in 10..13 {
+                    if (y, z) != (11, 11) || x == -1 || x == len {
+                        input.push((x, y, z));
+                    }
+                }
+            }
+        }
+        let pocket = 4 * len + 2;
+        assert_eq!(part2(&input), part1(&input) - pocket);
+        let tube_outside = 12 * (len + 2) + 18;
+        // Two of the bars each share a face with the first.
+        let tripod = 3 * (4 * 1100 + 2) - 2 * 2;
+        assert_eq!(part2(&input), tube_outside + tripod);
+        let mesh = build_mesh(&input, true);
+        assert_eq!(2, mesh.objects.len());
+        assert_eq!(pocket, area(&mesh.objects[1].1));
+    }
+
     #[test]
     fn part1_my_input() {
         let input = parse_input(MY_INPUT);
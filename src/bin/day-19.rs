@@ -1,200 +1,395 @@
 use std::str::FromStr;
 
+use aoc_2022::search::{self, BranchAndBound};
+
 const MY_INPUT: &str = include_str!("../../inputs/day-19.txt");
 
 fn main() {
     let input = parse_input(MY_INPUT);
+    if std::env::args().any(|arg| arg == "--plans") {
+        for (blueprint, plan) in input.iter().zip(best_plans(&input, 24)) {
+            println!("Blueprint {}:\n", blueprint.id);
+            println!("{}", plan.narrative(blueprint));
+        }
+    }
     println!("part1: {}", part1(&input));
     println!("part2: {}", part2(&input));
 }
 
 type Input = Vec<Blueprint>;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Resource {
-    Ore,
-    Clay,
-    Obsidian,
-    Geode,
+/// Resources are numbered in the order the blueprint describes the robots
+/// which collect them. There's one robot of the first kind to start with,
+/// and the last kind of resource is the one to collect as much of as
+/// possible.
+#[derive(Debug)]
+struct Blueprint {
+    id: u32,
+    resources: Vec<String>,
+    // costs[robot][r] is the amount of resource r needed to build the robot
+    // which collects resource robot.
+    costs: Vec<Vec<u32>>,
 }
 
-impl FromStr for Resource {
-    type Err = ();
+impl Blueprint {
+    fn goal(&self) -> usize {
+        self.resources.len() - 1
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "Ore" | "ore" => Ok(Self::Ore),
-            "Clay" | "clay" => Ok(Self::Clay),
-            "Obsidian" | "obsidian" => Ok(Self::Obsidian),
-            "Geode" | "geode" => Ok(Self::Geode),
-            _ => Err(()),
+    fn robot_name(&self, r: usize) -> String {
+        match self.resources[r].as_str() {
+            "geode" => "geode-cracking robot".to_string(),
+            name => format!("{name}-collecting robot"),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Robot {
-    // costs[r] is count of resource r needed to create the robot
-    costs: [u8; 4],
-}
-
-impl FromStr for Robot {
+impl FromStr for Blueprint {
     type Err = ();
 
+    // Blueprint 1: Each ore robot costs 4 ore. Each obsidian robot costs 3 ore and 14 clay. ...
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut costs: [u8; 4] = [0; 4];
-        let mut words = s.split_ascii_whitespace();
-        let count: u8 = words.nth(4).unwrap().parse().unwrap();
-        let resource: Resource = words.next().unwrap().parse().unwrap();
-        costs[resource as usize] = count;
-        while let Some("and") = words.next() {
-            let count: u8 = words.next().unwrap().parse().unwrap();
-            let resource: Resource = words.next().unwrap().parse().unwrap();
-            costs[resource as usize] = count;
+        let (id, robots) = s.split_once(':').ok_or(())?;
+        let id = id.strip_prefix("Blueprint ").ok_or(())?;
+        let id = id.trim().parse().map_err(|_| ())?;
+        let robots: Vec<Vec<&str>> = robots
+            .split('.')
+            .map(|sentence| {
+                sentence
+                    .split(|c: char| c.is_whitespace() || c == ',')
+                    .filter(|w| !w.is_empty())
+                    .collect()
+            })
+            .filter(|words: &Vec<&str>| !words.is_empty())
+            .collect();
+        let mut resources = vec![];
+        for words in &robots {
+            match words[..] {
+                ["Each", name, "robot", "costs", ..] => resources.push(name.to_string()),
+                _ => return Err(()),
+            }
+        }
+        let mut costs = vec![vec![0; resources.len()]; resources.len()];
+        for (robot, words) in robots.iter().enumerate() {
+            // "3 ore and 14 clay", or "1 ore, 2 clay and 3 obsidian".
+            let cost_words: Vec<_> = words[4..].iter().filter(|&&w| w != "and").collect();
+            if cost_words.len() % 2 != 0 {
+                return Err(());
+            }
+            for cost in cost_words.chunks(2) {
+                let [count, name] = cost[..] else {
+                    unreachable!()
+                };
+                let r = resources.iter().position(|r| r == name).ok_or(())?;
+                costs[robot][r] = count.parse().map_err(|_| ())?;
+            }
         }
-        Ok(Robot { costs })
+        Ok(Blueprint {
+            id,
+            resources,
+            costs,
+        })
     }
 }
 
-#[derive(Debug)]
-struct Blueprint {
-    id: u8,
-    // robots[r] is cost to produce robot which generates r.
-    robots: [Robot; 4],
+fn parse_input(s: &str) -> Input {
+    s.lines().map(|line| line.parse().unwrap()).collect()
 }
 
-fn parse_input(s: &str) -> Input {
-    s.lines()
-        .map(|line| {
-            let (id, robots) = line.split_once(':').unwrap();
-            let id: u8 = id.split_ascii_whitespace().nth(1).unwrap().parse().unwrap();
-            let mut robots = robots.split('.');
-            let ore_robot: Robot = robots.next().unwrap().parse().unwrap();
-            let clay_robot: Robot = robots.next().unwrap().parse().unwrap();
-            let obsidian_robot: Robot = robots.next().unwrap().parse().unwrap();
-            let geode_robot: Robot = robots.next().unwrap().parse().unwrap();
-            Blueprint {
-                id,
-                robots: [ore_robot, clay_robot, obsidian_robot, geode_robot],
-            }
-        })
-        .collect()
-}
-
-fn time_to_build(
-    robot_costs: &[Robot],
-    robots: &[u8],
-    resources: &[u32],
-    robot_type: Resource,
-) -> u8 {
-    let robot_type = robot_type as usize;
-    robot_costs[robot_type]
-        .costs
-        .into_iter()
+/// The number of minutes until a robot with the given costs is built, counting
+/// the minute spent building it, or `None` if the robots there are can't
+/// afford it within `u8::MAX` minutes.
+fn time_to_build(costs: &[u32], robots: &[u8], resources: &[u32]) -> Option<u8> {
+    costs
+        .iter()
         .enumerate()
-        .map(|(resource, resource_cost)| {
-            let resource_cost = resource_cost as u32;
+        .map(|(resource, &resource_cost)| {
             let curr_resource_amt = resources[resource];
             let added_per_turn = robots[resource] as u32;
             if curr_resource_amt >= resource_cost {
-                1
+                Some(1)
             } else if added_per_turn == 0 {
-                u8::MAX
+                None
             } else {
                 let needed = resource_cost - curr_resource_amt;
                 // turns = ceil(needed / added_per_turn)
-                let turns = (needed + added_per_turn - 1) / added_per_turn;
-                assert!(turns < u8::MAX as u32);
-                turns as u8 + 1
+                let turns = needed.div_ceil(added_per_turn);
+                u8::try_from(turns + 1).ok()
             }
         })
-        .max()
-        .unwrap()
+        .try_fold(1, |longest, turns| Some(longest.max(turns?)))
 }
 
-fn max_geodes(
-    robot_costs: &[Robot],
-    minutes_remaining: u8,
-    robots: &mut [u8],
-    resources: &mut [u32],
-    max: &mut u32,
-) {
-    // how many geodes we would create total if we built no more rebots
-    let current_heading: u32 = resources[Resource::Geode as usize]
-        + minutes_remaining as u32 * robots[Resource::Geode as usize] as u32;
-    if current_heading > *max {
-        *max = current_heading;
-    }
-    let n = minutes_remaining as u32;
-    let hypothetical_max = current_heading + (n * (n - 1)) / 2;
-    if hypothetical_max <= *max {
-        return;
-    }
-    for robot_type in [
-        Resource::Clay,
-        Resource::Geode,
-        Resource::Obsidian,
-        Resource::Ore,
-    ] {
-        if robot_type == Resource::Ore
-            && (minutes_remaining - 1)
-                <= robot_costs[Resource::Ore as usize].costs[Resource::Ore as usize]
-        {
-            // don't bother building an ore robot if the amount of ore it would
-            // produce over its lifetime is not sufficient to recoup its cost.
-            continue;
+struct GeodeSearch<'a> {
+    blueprint: &'a Blueprint,
+    // Only one robot can be built a minute, so there's no point collecting
+    // more of a resource a minute than the most any robot costs.
+    max_useful_robots: Vec<u32>,
+}
+
+impl GeodeSearch<'_> {
+    fn new(blueprint: &Blueprint) -> GeodeSearch {
+        let goal = blueprint.goal();
+        let max_useful_robots = (0..blueprint.resources.len())
+            .map(|r| {
+                if r == goal {
+                    u32::MAX
+                } else {
+                    blueprint.costs.iter().map(|costs| costs[r]).max().unwrap()
+                }
+            })
+            .collect();
+        GeodeSearch {
+            blueprint,
+            max_useful_robots,
         }
-        let time = time_to_build(robot_costs, robots, resources, robot_type);
-        if time >= minutes_remaining {
-            continue;
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SearchState {
+    minutes_remaining: u8,
+    robots: Vec<u8>,
+    resources: Vec<u32>,
+}
+
+impl BranchAndBound for GeodeSearch<'_> {
+    type State = SearchState;
+    type Score = u32;
+    type Key = (u8, Vec<u8>, Vec<u32>);
+
+    // how many geodes we would create total if we built no more robots
+    fn score(&self, state: &SearchState) -> u32 {
+        let goal = self.blueprint.goal();
+        state.resources[goal] + state.minutes_remaining as u32 * state.robots[goal] as u32
+    }
+
+    // Pretend each kind of robot is paid for out of its own copy of the
+    // resources, and that one of each kind can be built each minute. Any real
+    // plan works in this version too, and building every robot as soon as its
+    // copy can afford it gets at least as many robots of every kind by every
+    // minute as any other plan does.
+    fn upper_bound(&self, state: &SearchState) -> u32 {
+        let costs = &self.blueprint.costs;
+        let goal = self.blueprint.goal();
+        let mut robots: Vec<u32> = state.robots.iter().map(|&n| n as u32).collect();
+        let mut wallets = vec![state.resources.clone(); costs.len()];
+        let mut geodes = self.score(state);
+        for minutes_remaining in (1..state.minutes_remaining as u32).rev() {
+            let built: Vec<bool> = wallets
+                .iter()
+                .zip(costs)
+                .map(|(wallet, costs)| wallet.iter().zip(costs).all(|(have, need)| have >= need))
+                .collect();
+            for (robot, wallet) in wallets.iter_mut().enumerate() {
+                for r in 0..wallet.len() {
+                    if built[robot] {
+                        wallet[r] -= costs[robot][r];
+                    }
+                    wallet[r] += robots[r];
+                }
+            }
+            for (robot, &built) in built.iter().enumerate() {
+                if built {
+                    robots[robot] += 1;
+                    if robot == goal {
+                        geodes += minutes_remaining;
+                    }
+                }
+            }
         }
-        for i in 0..4 {
-            resources[i] += time as u32 * robots[i] as u32;
-            resources[i] -= robot_costs[robot_type as usize].costs[i] as u32;
+        geodes
+    }
+
+    fn successors(&self, state: &SearchState) -> Vec<SearchState> {
+        let goal = self.blueprint.goal();
+        let mut next = vec![];
+        for robot in (0..self.blueprint.resources.len()).rev() {
+            if state.robots[robot] as u32 >= self.max_useful_robots[robot] {
+                continue;
+            }
+            let costs = &self.blueprint.costs[robot];
+            let Some(time) = time_to_build(costs, &state.robots, &state.resources) else {
+                continue;
+            };
+            if time >= state.minutes_remaining {
+                continue;
+            }
+            // A robot that isn't collecting the goal needs to be ready in time
+            // for what it collects to go towards a goal robot which is then
+            // ready in time to collect something.
+            if robot != goal && state.minutes_remaining - time <= 2 {
+                continue;
+            }
+            let mut built = state.clone();
+            built.minutes_remaining -= time;
+            for ((resource, &robots), &cost) in
+                built.resources.iter_mut().zip(&state.robots).zip(costs)
+            {
+                *resource += time as u32 * robots as u32;
+                *resource -= cost;
+            }
+            built.robots[robot] += 1;
+            next.push(built);
         }
-        robots[robot_type as usize] += 1;
-        max_geodes(
-            robot_costs,
-            minutes_remaining - time,
-            robots,
-            resources,
-            max,
-        );
-        robots[robot_type as usize] -= 1;
-        for i in 0..4 {
-            resources[i] += robot_costs[robot_type as usize].costs[i] as u32;
-            resources[i] -= time as u32 * robots[i] as u32;
+        next
+    }
+
+    fn key(&self, state: &SearchState) -> Option<Self::Key> {
+        Some((
+            state.minutes_remaining,
+            state.robots.clone(),
+            state.resources.clone(),
+        ))
+    }
+}
+
+/// The robots to build for the most geodes from a blueprint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Plan {
+    minutes: u8,
+    /// Which robot to start building in which minute, in order. Minutes are
+    /// counted from 1, like the puzzle does.
+    builds: Vec<(u8, usize)>,
+    geodes: u32,
+}
+
+impl Plan {
+    /// Describes the plan minute by minute, in the words of the puzzle.
+    fn narrative(&self, blueprint: &Blueprint) -> String {
+        let names = &blueprint.resources;
+        let mut s = String::new();
+        let mut robots = vec![0; names.len()];
+        robots[0] = 1;
+        let mut resources = vec![0; names.len()];
+        let mut builds = self.builds.iter().peekable();
+        for minute in 1..=self.minutes {
+            s += &format!("== Minute {minute} ==\n");
+            let building = builds.next_if(|&&(m, _)| m == minute).map(|&(_, r)| r);
+            if let Some(robot) = building {
+                let costs = &blueprint.costs[robot];
+                let spent: Vec<_> = (0..names.len())
+                    .filter(|&r| costs[r] != 0)
+                    .map(|r| format!("{} {}", costs[r], names[r]))
+                    .collect();
+                let robot_name = blueprint.robot_name(robot);
+                let article = if robot_name.starts_with(['a', 'e', 'i', 'o', 'u']) {
+                    "an"
+                } else {
+                    "a"
+                };
+                s += &format!(
+                    "Spend {} to start building {article} {robot_name}.\n",
+                    spent.join(" and "),
+                );
+                for r in 0..names.len() {
+                    resources[r] -= costs[r];
+                }
+            }
+            for r in 0..names.len() {
+                let n = robots[r];
+                if n == 0 {
+                    continue;
+                }
+                resources[r] += n;
+                let total = resources[r];
+                let robot_name = blueprint.robot_name(r);
+                let name = &names[r];
+                let line = match (name.as_str(), n) {
+                    ("geode", 1) => format!(
+                        "1 geode-cracking robot cracks 1 geode; you now have {total} open geode{}.",
+                        if total == 1 { "" } else { "s" }
+                    ),
+                    ("geode", _) => format!(
+                        "{n} geode-cracking robots crack {n} geodes; you now have {total} open geodes."
+                    ),
+                    (_, 1) => format!("1 {robot_name} collects 1 {name}; you now have {total} {name}."),
+                    _ => format!("{n} {robot_name}s collect {n} {name}; you now have {total} {name}."),
+                };
+                s += &line;
+                s.push('\n');
+            }
+            if let Some(robot) = building {
+                robots[robot] += 1;
+                s += &format!(
+                    "The new {} is ready; you now have {} of them.\n",
+                    blueprint.robot_name(robot),
+                    robots[robot]
+                );
+            }
+            if minute < self.minutes {
+                s.push('\n');
+            }
         }
+        s
     }
 }
 
-fn part1(input: &Input) -> u32 {
-    input
-        .iter()
-        .map(|blueprint| {
-            let mut robots = [1, 0, 0, 0];
-            let mut resources: [u32; 4] = [0; 4];
-            let mut max = 0;
-            max_geodes(&blueprint.robots, 24, &mut robots, &mut resources, &mut max);
-            blueprint.id as u32 * max
+fn best_plan(blueprint: &Blueprint, minutes: u8) -> Plan {
+    let n = blueprint.resources.len();
+    let mut robots = vec![0; n];
+    robots[0] = 1;
+    let start = SearchState {
+        minutes_remaining: minutes,
+        robots,
+        resources: vec![0; n],
+    };
+    let solution = search::maximize(&GeodeSearch::new(blueprint), start);
+    let builds = solution
+        .path
+        .windows(2)
+        .map(|states| {
+            let (before, after) = (&states[0], &states[1]);
+            let robot = (0..n)
+                .find(|&r| after.robots[r] > before.robots[r])
+                .unwrap();
+            (minutes - after.minutes_remaining, robot)
         })
+        .collect();
+    Plan {
+        minutes,
+        builds,
+        geodes: solution.score,
+    }
+}
+
+/// Finds the best plan for each blueprint, searching them in parallel.
+fn best_plans(blueprints: &[Blueprint], minutes: u8) -> Vec<Plan> {
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = blueprints
+            .iter()
+            .map(|blueprint| scope.spawn(move || best_plan(blueprint, minutes)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+fn quality_level_sum(blueprints: &[Blueprint], minutes: u8) -> u32 {
+    blueprints
+        .iter()
+        .zip(best_plans(blueprints, minutes))
+        .map(|(blueprint, plan)| blueprint.id * plan.geodes)
         .sum()
 }
 
-fn part2(input: &Input) -> u32 {
-    input
+fn max_geodes_product(blueprints: &[Blueprint], minutes: u8) -> u32 {
+    best_plans(blueprints, minutes)
         .iter()
-        .take(3)
-        .map(|blueprint| {
-            let mut robots = [1, 0, 0, 0];
-            let mut resources: [u32; 4] = [0; 4];
-            let mut max = 0;
-            max_geodes(&blueprint.robots, 32, &mut robots, &mut resources, &mut max);
-            max
-        })
+        .map(|plan| plan.geodes)
         .product()
 }
 
+fn part1(input: &Input) -> u32 {
+    quality_level_sum(input, 24)
+}
+
+fn part2(input: &Input) -> u32 {
+    max_geodes_product(&input[..input.len().min(3)], 32)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -209,6 +404,129 @@ Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsid
         assert_eq!(part1(&input), 33);
     }
 
+    #[test]
+    fn part2_sample() {
+        let input = parse_input(SAMPLE);
+        assert_eq!(part2(&input), 56 * 62);
+    }
+
+    #[test]
+    fn narrative_sample() {
+        let input = parse_input(SAMPLE);
+        let plan = best_plan(&input[0], 24);
+        assert_eq!(9, plan.geodes);
+        assert!(plan
+            .narrative(&input[0])
+            .ends_with("you now have 9 open geodes.\n"));
+        // The plan from the puzzle, which isn't necessarily the one found.
+        let plan = Plan {
+            minutes: 24,
+            builds: vec![
+                (3, 1),
+                (5, 1),
+                (7, 1),
+                (11, 2),
+                (12, 1),
+                (15, 2),
+                (18, 3),
+                (21, 3),
+            ],
+            geodes: 9,
+        };
+        let narrative = plan.narrative(&input[0]);
+        assert!(narrative.starts_with(
+            "== Minute 1 ==
+1 ore-collecting robot collects 1 ore; you now have 1 ore.
+
+== Minute 2 ==
+1 ore-collecting robot collects 1 ore; you now have 2 ore.
+
+== Minute 3 ==
+Spend 2 ore to start building a clay-collecting robot.
+1 ore-collecting robot collects 1 ore; you now have 1 ore.
+The new clay-collecting robot is ready; you now have 1 of them.
+"
+        ));
+        assert!(narrative.contains(
+            "== Minute 18 ==
+Spend 2 ore and 7 obsidian to start building a geode-cracking robot.
+1 ore-collecting robot collects 1 ore; you now have 2 ore.
+4 clay-collecting robots collect 4 clay; you now have 17 clay.
+2 obsidian-collecting robots collect 2 obsidian; you now have 3 obsidian.
+The new geode-cracking robot is ready; you now have 1 of them.
+"
+        ));
+        assert!(narrative.ends_with(
+            "== Minute 24 ==
+1 ore-collecting robot collects 1 ore; you now have 6 ore.
+4 clay-collecting robots collect 4 clay; you now have 41 clay.
+2 obsidian-collecting robots collect 2 obsidian; you now have 8 obsidian.
+2 geode-cracking robots crack 2 geodes; you now have 9 open geodes.
+"
+        ));
+    }
+
+    // Tries building every robot it can, or nothing, every minute.
+    fn brute_force_max_geodes(blueprint: &Blueprint, minutes: u8) -> u32 {
+        let n = blueprint.resources.len();
+        let mut robots = vec![0; n];
+        robots[0] = 1;
+        let mut states = std::collections::HashSet::from([(robots, vec![0; n])]);
+        for _ in 0..minutes {
+            let mut next = std::collections::HashSet::new();
+            for (robots, resources) in states {
+                let mut collected = resources.clone();
+                for r in 0..n {
+                    collected[r] += robots[r];
+                }
+                for (robot, costs) in blueprint.costs.iter().enumerate() {
+                    if (0..n).all(|r| resources[r] >= costs[r]) {
+                        let mut robots = robots.clone();
+                        robots[robot] += 1;
+                        let resources = (0..n).map(|r| collected[r] - costs[r]).collect();
+                        next.insert((robots, resources));
+                    }
+                }
+                next.insert((robots, collected));
+            }
+            states = next;
+        }
+        let goal = blueprint.goal();
+        states
+            .iter()
+            .map(|(_, resources)| resources[goal])
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn other_tiers() {
+        let input = parse_input(
+            "Blueprint 1: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each geode robot costs 2 ore and 4 clay.
+Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 1 ore. Each obsidian robot costs 1 ore and 2 clay. Each crystal robot costs 2 clay, 2 obsidian and 1 ore. Each geode robot costs 2 crystal and 1 obsidian.",
+        );
+        assert_eq!(vec!["ore", "clay", "geode"], input[0].resources);
+        assert_eq!(vec![1, 2, 2, 0, 0], input[1].costs[3]);
+        for (blueprint, minutes) in [(&input[0], 13), (&input[1], 12)] {
+            let geodes = brute_force_max_geodes(blueprint, minutes);
+            assert!(geodes > 0);
+            assert_eq!(geodes, best_plan(blueprint, minutes).geodes);
+        }
+    }
+
+    #[test]
+    fn expensive_robots() {
+        assert_eq!(Some(4), time_to_build(&[3, 0], &[1, 0], &[0, 0]));
+        assert_eq!(Some(1), time_to_build(&[3, 0], &[0, 0], &[5, 0]));
+        assert_eq!(None, time_to_build(&[3, 1], &[1, 0], &[0, 0]));
+        assert_eq!(None, time_to_build(&[1000], &[1], &[0]));
+        let input = parse_input(
+            "Blueprint 1: Each ore robot costs 900 ore. Each clay robot costs 2 ore. Each geode robot costs 2 ore and 1000 clay.",
+        );
+        assert_eq!(0, best_plan(&input[0], 24).geodes);
+        assert_eq!(0, part1(&input));
+    }
+
     #[test]
     fn part1_my_input() {
         let input = parse_input(MY_INPUT);
@@ -14,103 +14,221 @@ fn parse_input(s: &str) -> Input {
     s.lines().map(|l| l.parse().unwrap()).collect()
 }
 
-/// returns a pair, (prevs, nexts), such that for all i, 0 <= i < input.len():
-/// * nexts[prevs[i]] = i
-/// * prevs[nexts[i]] = i
-/// The prevs and nexts indices form a kind of doubly linked list of the elements
-/// of the input.
-fn mk_list(input: &Input) -> (Vec<usize>, Vec<usize>) {
-    let n = input.len();
-    let mut prevs = Vec::with_capacity(input.len());
-    let mut nexts = Vec::with_capacity(input.len());
-    for i in 0..n {
-        let prev = if i == 0 { n - 1 } else { i - 1 };
-        let next = (i + 1) % n;
-        prevs.push(prev);
-        nexts.push(next);
-    }
-    (prevs, nexts)
+const NIL: usize = usize::MAX;
+
+/// A sequence of the ids 0..n in some order, stored as an implicit treap so
+/// that finding where an id is, removing it and inserting it somewhere else
+/// all take O(log n).
+///
+/// Each node is an id. A node's position in the sequence is the number of
+/// nodes before it in an in-order walk, which is tracked with subtree sizes.
+struct OrderTree {
+    root: usize,
+    left: Vec<usize>,
+    right: Vec<usize>,
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    priority: Vec<u64>,
 }
 
-fn mix(input: &Input, num_iters: usize) -> i64 {
-    let (mut prevs, mut nexts) = mk_list(input);
-    let n = input.len();
-    for _ in 0..num_iters {
-        for (i, val) in input.iter().enumerate() {
-            match val.cmp(&0) {
-                Ordering::Less => {
-                    // splice out this number.
-                    let prev = prevs[i];
-                    let next = nexts[i];
-                    nexts[prev] = next;
-                    prevs[next] = prev;
-                    // splice it in at the correct location
-                    let mut parent = next;
-                    let val = (-*val as usize) % (n - 1);
-                    for _ in 0..val {
-                        parent = prevs[parent];
-                    }
-                    // parent is now the new successor
-                    let parent_prev = prevs[parent];
-                    prevs[parent] = i;
-                    nexts[i] = parent;
-                    nexts[parent_prev] = i;
-                    prevs[i] = parent_prev;
-                }
-                Ordering::Equal => {
-                    // nothing
-                }
+impl OrderTree {
+    /// Returns the sequence 0, 1, ..., n - 1.
+    fn new(n: usize) -> OrderTree {
+        let mut rng_state: u64 = 0x2545_f491_4f6c_dd1d;
+        let priority = (0..n)
+            .map(|_| {
+                // xorshift64
+                rng_state ^= rng_state << 13;
+                rng_state ^= rng_state >> 7;
+                rng_state ^= rng_state << 17;
+                rng_state
+            })
+            .collect();
+        let mut tree = OrderTree {
+            root: NIL,
+            left: vec![NIL; n],
+            right: vec![NIL; n],
+            parent: vec![NIL; n],
+            size: vec![1; n],
+            priority,
+        };
+        for id in 0..n {
+            tree.root = tree.merge(tree.root, id);
+        }
+        tree
+    }
+
+    fn subtree_size(&self, node: usize) -> usize {
+        if node == NIL {
+            0
+        } else {
+            self.size[node]
+        }
+    }
+
+    fn set_left(&mut self, node: usize, child: usize) {
+        self.left[node] = child;
+        if child != NIL {
+            self.parent[child] = node;
+        }
+        self.size[node] = 1 + self.subtree_size(child) + self.subtree_size(self.right[node]);
+    }
+
+    fn set_right(&mut self, node: usize, child: usize) {
+        self.right[node] = child;
+        if child != NIL {
+            self.parent[child] = node;
+        }
+        self.size[node] = 1 + self.subtree_size(self.left[node]) + self.subtree_size(child);
+    }
+
+    /// Joins two trees where everything in `a` comes before everything in
+    /// `b`, returning the new root.
+    fn merge(&mut self, a: usize, b: usize) -> usize {
+        let root = if a == NIL {
+            b
+        } else if b == NIL {
+            a
+        } else if self.priority[a] > self.priority[b] {
+            let right = self.merge(self.right[a], b);
+            self.set_right(a, right);
+            a
+        } else {
+            let left = self.merge(a, self.left[b]);
+            self.set_left(b, left);
+            b
+        };
+        if root != NIL {
+            self.parent[root] = NIL;
+        }
+        root
+    }
+
+    /// Splits a tree into its first `k` nodes and the rest.
+    fn split(&mut self, node: usize, k: usize) -> (usize, usize) {
+        if node == NIL {
+            return (NIL, NIL);
+        }
+        let left_size = self.subtree_size(self.left[node]);
+        let (a, b) = if k <= left_size {
+            let (a, b) = self.split(self.left[node], k);
+            self.set_left(node, b);
+            (a, node)
+        } else {
+            let (a, b) = self.split(self.right[node], k - left_size - 1);
+            self.set_right(node, a);
+            (node, b)
+        };
+        for root in [a, b] {
+            if root != NIL {
+                self.parent[root] = NIL;
+            }
+        }
+        (a, b)
+    }
+
+    /// The position of `id` in the sequence.
+    fn index_of(&self, id: usize) -> usize {
+        let mut index = self.subtree_size(self.left[id]);
+        let mut node = id;
+        while self.parent[node] != NIL {
+            let parent = self.parent[node];
+            if self.right[parent] == node {
+                index += self.subtree_size(self.left[parent]) + 1;
+            }
+            node = parent;
+        }
+        index
+    }
+
+    /// The id at position `index` in the sequence.
+    fn nth(&self, mut index: usize) -> usize {
+        let mut node = self.root;
+        loop {
+            let left_size = self.subtree_size(self.left[node]);
+            match index.cmp(&left_size) {
+                Ordering::Less => node = self.left[node],
+                Ordering::Equal => return node,
                 Ordering::Greater => {
-                    // move forward
-                    // splice out this number.
-                    let prev = prevs[i];
-                    let next = nexts[i];
-                    nexts[prev] = next;
-                    prevs[next] = prev;
-                    // splice it in at the correct location
-                    let val: usize = *val as usize % (n - 1);
-                    let mut parent = prev;
-                    for _ in 0..val {
-                        parent = nexts[parent];
-                    }
-                    // parent is now the new prededessor
-                    let parent_next = nexts[parent];
-                    nexts[parent] = i;
-                    prevs[i] = parent;
-                    prevs[parent_next] = i;
-                    nexts[i] = parent_next;
+                    index -= left_size + 1;
+                    node = self.right[node];
                 }
             }
         }
     }
-    grove_coordinates(input, &nexts)
+
+    /// Takes `id` out of the sequence, returning where it was.
+    fn remove(&mut self, id: usize) -> usize {
+        let index = self.index_of(id);
+        let (a, rest) = self.split(self.root, index);
+        let (_, b) = self.split(rest, 1);
+        self.root = self.merge(a, b);
+        index
+    }
+
+    /// Puts `id`, which must not be in the sequence, at position `index`.
+    fn insert(&mut self, id: usize, index: usize) {
+        self.left[id] = NIL;
+        self.right[id] = NIL;
+        self.size[id] = 1;
+        let (a, b) = self.split(self.root, index);
+        let a = self.merge(a, id);
+        self.root = self.merge(a, b);
+    }
 }
 
-fn grove_coordinates(input: &Input, nexts: &[usize]) -> i64 {
-    [1000, 2000, 3000]
-        .into_iter()
-        .map(|i| num_at(input, nexts, i))
-        .sum()
+/// Mixes the numbers `rounds` times, returning the order they end up in as
+/// indices into `nums`.
+fn mix(nums: &[i64], rounds: usize) -> OrderTree {
+    let mut order = OrderTree::new(nums.len());
+    // Moving a number all the way round the others puts it back where it
+    // started.
+    let others = nums.len() as i64 - 1;
+    for _ in 0..rounds {
+        for (id, &num) in nums.iter().enumerate() {
+            if others == 0 || num % others == 0 {
+                continue;
+            }
+            let from = order.remove(id) as i64;
+            let to = (from + num).rem_euclid(others);
+            order.insert(id, to as usize);
+        }
+    }
+    order
 }
 
-fn part1(input: &Input) -> i64 {
-    mix(input, 1)
+/// Multiplies every number by `key`, mixes them `rounds` times, and adds up
+/// the numbers `offsets` after the 0.
+///
+/// The products can be too big for an `i64`, but a number moves the same way
+/// if it's changed by a multiple of how many others there are, so the mixing
+/// only needs the products modulo that. The answer is added up in full.
+fn decrypt(input: &Input, key: i64, rounds: usize, offsets: &[usize]) -> i128 {
+    let product = |n: i64| n as i128 * key as i128;
+    let others = (input.len() as i128 - 1).max(1);
+    let steps: Vec<_> = input
+        .iter()
+        .map(|&n| product(n).rem_euclid(others) as i64)
+        .collect();
+    let order = mix(&steps, rounds);
+    let zero = input.iter().position(|&n| n == 0).unwrap();
+    let zero_index = order.index_of(zero);
+    offsets
+        .iter()
+        .map(|offset| product(input[order.nth((zero_index + offset) % input.len())]))
+        .sum()
 }
 
-fn num_at(vals: &[i64], nexts: &[usize], i: usize) -> i64 {
-    let mut idx = vals.iter().copied().position(|e| e == 0).unwrap();
-    let i = i % vals.len();
-    for _ in 0..i {
-        idx = nexts[idx];
-    }
-    vals[idx]
+const GROVE_OFFSETS: [usize; 3] = [1000, 2000, 3000];
+
+fn part1(input: &Input) -> i128 {
+    decrypt(input, 1, 1, &GROVE_OFFSETS)
 }
 
 const DECRYPTION_KEY: i64 = 811589153;
 
-fn part2(input: &Input) -> i64 {
-    let input: Vec<_> = input.iter().copied().map(|n| n * DECRYPTION_KEY).collect();
-    mix(&input, 10)
+fn part2(input: &Input) -> i128 {
+    decrypt(input, DECRYPTION_KEY, 10, &GROVE_OFFSETS)
 }
 
 #[cfg(test)]
@@ -125,6 +243,40 @@ mod tests {
 0
 4";
 
+    fn to_vec(tree: &OrderTree) -> Vec<usize> {
+        (0..tree.subtree_size(tree.root))
+            .map(|i| tree.nth(i))
+            .collect()
+    }
+
+    #[test]
+    fn order_tree() {
+        let mut tree = OrderTree::new(100);
+        let mut model: Vec<usize> = (0..100).collect();
+        let mut x = 7;
+        for _ in 0..1000 {
+            x = (x * 31 + 17) % 1009;
+            let id = x % 100;
+            let index = model.iter().position(|&e| e == id).unwrap();
+            assert_eq!(index, tree.index_of(id));
+            assert_eq!(index, tree.remove(id));
+            model.remove(index);
+            let to = (x * 7) % 100;
+            model.insert(to, id);
+            tree.insert(id, to);
+        }
+        assert_eq!(model, to_vec(&tree));
+    }
+
+    #[test]
+    fn mix_sample() {
+        let input = parse_input(SAMPLE);
+        let order = mix(&input, 1);
+        let mixed: Vec<_> = to_vec(&order).into_iter().map(|i| input[i]).collect();
+        // The puzzle shows the same cycle starting from 1.
+        assert_eq!(vec![-2, 1, 2, -3, 4, 0, 3], mixed);
+    }
+
     #[test]
     fn part1_sample() {
         let input = parse_input(SAMPLE);
@@ -137,6 +289,30 @@ mod tests {
         assert_eq!(part2(&input), 1623178306);
     }
 
+    #[test]
+    fn huge_products() {
+        let input = vec![i64::MAX, 0, i64::MIN, 3, -7];
+        let keyed: Vec<_> = input
+            .iter()
+            .map(|&n| n as i128 * DECRYPTION_KEY as i128)
+            .collect();
+        // Mix the products themselves, one slow step at a time.
+        let mut order: Vec<usize> = (0..input.len()).collect();
+        for _ in 0..10 {
+            for (id, &num) in keyed.iter().enumerate() {
+                let from = order.iter().position(|&i| i == id).unwrap();
+                order.remove(from);
+                let to = (from as i128 + num).rem_euclid(order.len() as i128);
+                order.insert(to as usize, id);
+            }
+        }
+        let zero = order.iter().position(|&i| i == 1).unwrap();
+        let expected: i128 = (1..=3)
+            .map(|offset| keyed[order[(zero + offset) % 5]])
+            .sum();
+        assert_eq!(expected, decrypt(&input, DECRYPTION_KEY, 10, &[1, 2, 3]));
+    }
+
     #[test]
     fn part1_my_input() {
         let input = parse_input(MY_INPUT);
@@ -1,16 +1,24 @@
-use std::cmp::Ordering;
-use std::collections::HashMap;
-use std::collections::HashSet;
 use std::str::FromStr;
 
+use aoc_2022::math;
+use aoc_2022::{Interner, Symbol, SymbolMap};
+
 const MY_INPUT: &str = include_str!("../../inputs/day-21.txt");
 
 fn main() {
     let input = parse_input(MY_INPUT);
+    // `--show <monkey>` prints what a monkey shouts in terms of humn.
+    let args: Vec<String> = std::env::args().collect();
+    for pair in args.windows(2).filter(|pair| pair[0] == "--show") {
+        let var = input.symbol(&pair[1]);
+        let tree = expand(var, &[input.symbol("humn")], &input).unwrap();
+        println!("{} = {}", pair[1], infix(&simplify(tree), &input.names));
+    }
     println!("part1: {}", part1(&input));
     println!("part2: {}", part2(&input));
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Op {
     Add,
     Sub,
@@ -18,6 +26,24 @@ enum Op {
     Div,
 }
 
+impl Op {
+    fn precedence(self) -> u8 {
+        match self {
+            Op::Add | Op::Sub => 1,
+            Op::Mult | Op::Div => 2,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Op::Add => "+",
+            Op::Sub => "-",
+            Op::Mult => "*",
+            Op::Div => "/",
+        }
+    }
+}
+
 impl FromStr for Op {
     type Err = ();
 
@@ -32,16 +58,30 @@ impl FromStr for Op {
     }
 }
 
-enum Expr<'a> {
+enum Expr {
     Num(i64),
-    Bin(&'a str, Op, &'a str),
+    Bin(Symbol, Op, Symbol),
 }
 
-struct Input<'a> {
-    defs: HashMap<&'a str, Expr<'a>>,
+struct Input {
+    names: Interner,
+    defs: SymbolMap<Expr>,
+}
+
+impl Input {
+    fn symbol(&self, name: &str) -> Symbol {
+        self.names
+            .get(name)
+            .unwrap_or_else(|| panic!("no monkey named {name}"))
+    }
+
+    fn def(&self, var: Symbol) -> Result<&Expr, Error> {
+        self.defs.get(var).ok_or(Error::Undefined(var))
+    }
 }
 
 fn parse_input(s: &str) -> Input {
+    let mut names = Interner::new();
     let defs = s
         .lines()
         .map(|line| {
@@ -54,111 +94,330 @@ fn parse_input(s: &str) -> Input {
                     let op = rhs.next().unwrap().parse::<Op>().unwrap();
                     let b = rhs.next().unwrap();
                     assert!(rhs.next().is_none());
-                    Expr::Bin(a, op, b)
+                    Expr::Bin(names.intern(a), op, names.intern(b))
                 }
             };
-            (var, expr)
+            (names.intern(var), expr)
         })
         .collect();
-    Input { defs }
+    Input { names, defs }
 }
 
 fn part1(input: &Input) -> i64 {
-    let mut resolved: HashMap<&str, i64> = HashMap::new();
-    resolve("root", input, &mut resolved)
+    let mut resolved = SymbolMap::new();
+    eval(input.symbol("root"), input, &mut resolved).unwrap()
 }
 
-fn resolve<'a>(var: &'a str, input: &Input<'a>, resolved: &mut HashMap<&'a str, i64>) -> i64 {
-    if let Some(&val) = resolved.get(var) {
-        return val;
+/// Works out what `var` shouts. A monkey is `None` in `resolved` while its
+/// operands are being worked out, so meeting it again means it's defined in
+/// terms of itself.
+fn eval(var: Symbol, input: &Input, resolved: &mut SymbolMap<Option<i64>>) -> Result<i64, Error> {
+    match resolved.get(var) {
+        Some(&Some(val)) => return Ok(val),
+        Some(None) => return Err(Error::Cycle(var)),
+        None => (),
     }
-    let val = match &input.defs[var] {
+    resolved.insert(var, None);
+    let val = match input.def(var)? {
         Expr::Num(i) => *i,
         Expr::Bin(a, op, b) => {
-            let a = resolve(a, input, resolved);
-            let b = resolve(b, input, resolved);
+            let a = eval(*a, input, resolved)?;
+            let b = eval(*b, input, resolved)?;
             match op {
-                Op::Add => a + b,
-                Op::Sub => a - b,
-                Op::Mult => a * b,
-                Op::Div => a / b,
+                Op::Add => a.checked_add(b).ok_or(Error::Overflow)?,
+                Op::Sub => a.checked_sub(b).ok_or(Error::Overflow)?,
+                Op::Mult => a.checked_mul(b).ok_or(Error::Overflow)?,
+                Op::Div if b == 0 => return Err(Error::DivisionByZero),
+                Op::Div if a.checked_rem(b).ok_or(Error::Overflow)? != 0 => {
+                    return Err(Error::InexactDivision(a, b))
+                }
+                Op::Div => a.checked_div(b).ok_or(Error::Overflow)?,
             }
         }
     };
-    resolved.insert(var, val);
-    val
-}
-
-// Finds all the variables which var transitively depends upon.
-fn depends_on<'a>(var: &'a str, input: &Input<'a>) -> HashSet<&'a str> {
-    let mut discovered = HashSet::new();
-    let mut frontier = vec![var];
-    while let Some(var) = frontier.pop() {
-        if let Expr::Bin(a, _, b) = input.defs[var] {
-            for dep in [a, b] {
-                if discovered.insert(dep) {
-                    frontier.push(dep);
+    resolved.insert(var, Some(val));
+    Ok(val)
+}
+
+/// An exact fraction, kept in lowest terms with a positive denominator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Ratio {
+    num: i128,
+    den: i128,
+}
+
+impl Ratio {
+    /// `num / den` in lowest terms, or `None` if that doesn't fit.
+    fn new(num: i128, den: i128) -> Option<Ratio> {
+        assert!(den != 0);
+        let g = math::gcd(num, den)? * den.signum();
+        Some(Ratio {
+            num: num.checked_div(g)?,
+            den: den.checked_div(g)?,
+        })
+    }
+
+    fn int(n: i64) -> Ratio {
+        Ratio {
+            num: n as i128,
+            den: 1,
+        }
+    }
+
+    fn is_zero(self) -> bool {
+        self.num == 0
+    }
+
+    fn is_integer(self) -> bool {
+        self.den == 1
+    }
+
+    fn apply(self, op: Op, other: Ratio) -> Result<Ratio, Error> {
+        let (a, b, c, d) = (self.num, self.den, other.num, other.den);
+        let (num, den) = match op {
+            Op::Add => (
+                a.checked_mul(d)
+                    .zip(c.checked_mul(b))
+                    .and_then(|(x, y)| x.checked_add(y)),
+                b.checked_mul(d),
+            ),
+            Op::Sub => (
+                a.checked_mul(d)
+                    .zip(c.checked_mul(b))
+                    .and_then(|(x, y)| x.checked_sub(y)),
+                b.checked_mul(d),
+            ),
+            Op::Mult => (a.checked_mul(c), b.checked_mul(d)),
+            Op::Div => {
+                if other.is_zero() {
+                    return Err(Error::DivisionByZero);
                 }
+                (a.checked_mul(d), b.checked_mul(c))
             }
+        };
+        match (num, den) {
+            (Some(num), Some(den)) => Ratio::new(num, den).ok_or(Error::Overflow),
+            _ => Err(Error::Overflow),
         }
     }
-    discovered
 }
 
-fn part2(input: &Input) -> i64 {
-    let (left, right) = match input.defs["root"] {
-        Expr::Num(_) => panic!("root resolved to a number"),
-        Expr::Bin(l, _, r) => (l, r),
+impl std::fmt::Display for Ratio {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Error {
+    Undefined(Symbol),
+    /// The monkey's shout depends on itself.
+    Cycle(Symbol),
+    InexactDivision(i64, i64),
+    RootIsNumber,
+    /// The root's two sides don't depend on the unknown.
+    UnknownNotUsed,
+    /// The unknown is needed on both sides of some operation, so it can't be
+    /// solved for by undoing one operation at a time.
+    UnknownOnBothSides,
+    NoSolution,
+    /// Every value of the unknown (or every non-zero one) is a solution.
+    NotUnique,
+    NotInteger(Ratio),
+    /// With the answer put back in, the monkeys' arithmetic gives these
+    /// different values for the two sides.
+    SidesDiffer(i64, i64),
+    DivisionByZero,
+    Overflow,
+}
+
+/// A monkey's expression with the definitions of the monkeys it depends on
+/// written out in full, apart from the ones left as variables.
+#[derive(Debug, PartialEq, Eq)]
+enum Tree {
+    Var(Symbol),
+    Num(Ratio),
+    Bin(Box<Tree>, Op, Box<Tree>),
+}
+
+fn expand(var: Symbol, vars: &[Symbol], input: &Input) -> Result<Tree, Error> {
+    fn go(
+        var: Symbol,
+        vars: &[Symbol],
+        input: &Input,
+        visiting: &mut SymbolMap<()>,
+    ) -> Result<Tree, Error> {
+        if vars.contains(&var) {
+            return Ok(Tree::Var(var));
+        }
+        if visiting.insert(var, ()).is_some() {
+            return Err(Error::Cycle(var));
+        }
+        let tree = match input.def(var)? {
+            Expr::Num(n) => Tree::Num(Ratio::int(*n)),
+            Expr::Bin(a, op, b) => {
+                let a = go(*a, vars, input, visiting)?;
+                let b = go(*b, vars, input, visiting)?;
+                Tree::Bin(Box::new(a), *op, Box::new(b))
+            }
+        };
+        visiting.remove(var);
+        Ok(tree)
+    }
+    go(var, vars, input, &mut SymbolMap::new())
+}
+
+/// Evaluates every part of the tree that doesn't depend on a variable,
+/// exactly, even where the division isn't.
+fn fold_constants(tree: Tree) -> Result<Tree, Error> {
+    let Tree::Bin(a, op, b) = tree else {
+        return Ok(tree);
     };
-    let left_deps = depends_on(left, input);
-    let right_deps = depends_on(right, input);
-    let (depends_on_humn, doesnt_depend_on_humn) = if left_deps.contains("humn") {
-        (left, right)
-    } else {
-        assert!(right_deps.contains("humn"));
-        (right, left)
+    match (fold_constants(*a)?, fold_constants(*b)?) {
+        (Tree::Num(a), Tree::Num(b)) => Ok(Tree::Num(a.apply(op, b)?)),
+        (a, b) => Ok(Tree::Bin(Box::new(a), op, Box::new(b))),
+    }
+}
+
+/// Evaluates constant parts of the tree where the monkeys would get the same
+/// answer, and removes adding 0, multiplying by 1 and so on. Multiplying by
+/// 0 gives 0 even if the other side can't be evaluated.
+fn simplify(tree: Tree) -> Tree {
+    let Tree::Bin(a, op, b) = tree else {
+        return tree;
     };
-    let mut resolved: HashMap<&str, i64> = HashMap::new();
-    let target_val = resolve(doesnt_depend_on_humn, input, &mut resolved);
-    let resolved = resolved;
-    let mut lo = 1;
-    match try_humn_val(lo, depends_on_humn, input, &resolved).cmp(&target_val) {
-        Ordering::Less => panic!("1 is too high"),
-        Ordering::Equal => return lo,
-        Ordering::Greater => (),
-    }
-    let mut hi = lo * 2;
-    loop {
-        match try_humn_val(hi, depends_on_humn, input, &resolved).cmp(&target_val) {
-            Ordering::Greater => hi *= 2,
-            Ordering::Equal => return hi,
-            Ordering::Less => break,
-        }
+    let zero = Ratio::int(0);
+    let one = Ratio::int(1);
+    match (simplify(*a), op, simplify(*b)) {
+        (Tree::Num(a), op, Tree::Num(b)) => match a.apply(op, b) {
+            Ok(val) if val.is_integer() => Tree::Num(val),
+            _ => Tree::Bin(Box::new(Tree::Num(a)), op, Box::new(Tree::Num(b))),
+        },
+        (x, Op::Add, Tree::Num(n)) | (Tree::Num(n), Op::Add, x) if n == zero => x,
+        (x, Op::Sub, Tree::Num(n)) if n == zero => x,
+        (x, Op::Mult, Tree::Num(n)) | (Tree::Num(n), Op::Mult, x) if n == one => x,
+        (x, Op::Div, Tree::Num(n)) if n == one => x,
+        (_, Op::Mult, Tree::Num(n)) | (Tree::Num(n), Op::Mult, _) if n == zero => Tree::Num(zero),
+        (a, op, b) => Tree::Bin(Box::new(a), op, Box::new(b)),
     }
-    // lo < hi
-    // f(lo) > target.  f(hi) < target.  Can now binary search between them.
-    // some value k, lo < k < hi, exists st f(k) == target
-    while hi - lo > 1 {
-        let mid = lo + (hi - lo) / 2;
-        match try_humn_val(mid, depends_on_humn, input, &resolved).cmp(&target_val) {
-            Ordering::Greater => lo = mid,
-            Ordering::Equal => {
-                let mut cand = mid;
-                while try_humn_val(cand - 1, depends_on_humn, input, &resolved) == target_val {
-                    cand -= 1;
-                }
-                return cand;
+}
+
+/// Writes the tree out with only the parentheses it needs. Operators of
+/// equal precedence group to the left, so the right operand of `-` and `/`
+/// keeps its parentheses, as does a division on the right of `*` since
+/// monkeys' division isn't exact. Negative numbers on the right get them too.
+fn infix(tree: &Tree, names: &Interner) -> String {
+    let precedence = |tree: &Tree| match tree {
+        Tree::Bin(_, op, _) => op.precedence(),
+        Tree::Num(n) if !n.is_integer() => Op::Div.precedence(),
+        _ => u8::MAX,
+    };
+    match tree {
+        Tree::Var(var) => names.resolve(*var).to_string(),
+        Tree::Num(n) => n.to_string(),
+        Tree::Bin(a, op, b) => {
+            let mut a_str = infix(a, names);
+            if precedence(a) < op.precedence() {
+                a_str = format!("({a_str})");
+            }
+            let mut b_str = infix(b, names);
+            let b_is_div = matches!(**b, Tree::Bin(_, Op::Div, _))
+                || matches!(**b, Tree::Num(n) if !n.is_integer());
+            let b_is_negative = matches!(**b, Tree::Num(n) if n.num < 0);
+            if b_is_negative
+                || precedence(b) < op.precedence()
+                || (precedence(b) == op.precedence()
+                    && (matches!(op, Op::Sub | Op::Div) || b_is_div))
+            {
+                b_str = format!("({b_str})");
             }
-            Ordering::Less => hi = mid,
+            format!("{a_str} {} {b_str}", op.as_str())
         }
     }
-    panic!("binary search failed");
 }
 
-fn try_humn_val(val: i64, root: &str, input: &Input, resolved: &HashMap<&str, i64>) -> i64 {
-    let mut resolved = resolved.clone();
-    resolved.insert("humn", val);
-    resolve(root, input, &mut resolved)
+/// Finds the value of the unknown which makes `tree` equal `target`, by
+/// undoing each operation on the way down to it.
+fn invert(mut tree: &Tree, mut target: Ratio) -> Result<Ratio, Error> {
+    // The value of c * x, or x * c, is target.
+    let undo_mult = |c: Ratio, target: Ratio| match (c.is_zero(), target.is_zero()) {
+        (true, true) => Err(Error::NotUnique),
+        (true, false) => Err(Error::NoSolution),
+        (false, _) => target.apply(Op::Div, c),
+    };
+    loop {
+        let (unknown, next_target) = match tree {
+            Tree::Var(_) => return Ok(target),
+            Tree::Num(_) => unreachable!("no unknown in tree"),
+            Tree::Bin(a, op, b) => match (&**a, &**b) {
+                (x, &Tree::Num(c)) => {
+                    let next_target = match op {
+                        Op::Add => target.apply(Op::Sub, c)?,
+                        Op::Sub => target.apply(Op::Add, c)?,
+                        Op::Mult => undo_mult(c, target)?,
+                        Op::Div if c.is_zero() => return Err(Error::DivisionByZero),
+                        Op::Div => target.apply(Op::Mult, c)?,
+                    };
+                    (x, next_target)
+                }
+                (&Tree::Num(c), x) => {
+                    let next_target = match op {
+                        Op::Add => target.apply(Op::Sub, c)?,
+                        Op::Sub => c.apply(Op::Sub, target)?,
+                        Op::Mult => undo_mult(c, target)?,
+                        // c / x is never 0 unless c is, and then x can be
+                        // anything but 0.
+                        Op::Div => match (c.is_zero(), target.is_zero()) {
+                            (true, true) => return Err(Error::NotUnique),
+                            (true, false) | (false, true) => return Err(Error::NoSolution),
+                            (false, false) => c.apply(Op::Div, target)?,
+                        },
+                    };
+                    (x, next_target)
+                }
+                _ => return Err(Error::UnknownOnBothSides),
+            },
+        };
+        tree = unknown;
+        target = next_target;
+    }
+}
+
+/// Finds the value `unknown` must shout for both sides of `root` to be
+/// equal, ignoring the unknown's own definition and root's operation. The
+/// answer is checked with the monkeys' own arithmetic, which can't divide
+/// inexactly.
+fn solve(input: &Input, unknown: Symbol, root: Symbol) -> Result<i64, Error> {
+    let &Expr::Bin(left_var, _, right_var) = input.def(root)? else {
+        return Err(Error::RootIsNumber);
+    };
+    let left = fold_constants(expand(left_var, &[unknown], input)?)?;
+    let right = fold_constants(expand(right_var, &[unknown], input)?)?;
+    let val = match (&left, &right) {
+        (Tree::Num(_), Tree::Num(_)) => return Err(Error::UnknownNotUsed),
+        (Tree::Num(target), tree) | (tree, Tree::Num(target)) => invert(tree, *target)?,
+        _ => return Err(Error::UnknownOnBothSides),
+    };
+    if val.den != 1 {
+        return Err(Error::NotInteger(val));
+    }
+    let val = i64::try_from(val.num).map_err(|_| Error::Overflow)?;
+    let mut resolved = SymbolMap::new();
+    resolved.insert(unknown, Some(val));
+    let left = eval(left_var, input, &mut resolved)?;
+    let right = eval(right_var, input, &mut resolved)?;
+    if left != right {
+        return Err(Error::SidesDiffer(left, right));
+    }
+    Ok(val)
+}
+
+fn part2(input: &Input) -> i64 {
+    solve(input, input.symbol("humn"), input.symbol("root")).unwrap()
 }
 
 #[cfg(test)]
@@ -193,6 +452,148 @@ hmdt: 32";
         assert_eq!(part2(&input), 301);
     }
 
+    #[test]
+    fn solve_for_any_monkey() {
+        let input = parse_input(SAMPLE);
+        let solve = |unknown, root| solve(&input, input.symbol(unknown), input.symbol(root));
+        // sjmn is 150, so pppw = (sllz + lgvd) / 4 = (sllz + 4) / 4 must be too.
+        assert_eq!(Ok(596), solve("sllz", "root"));
+        // pppw is 2, so sjmn = (hmdt - 2) * 5 must be too.
+        assert_eq!(
+            Err(Error::NotInteger(Ratio::new(12, 5).unwrap())),
+            solve("hmdt", "root")
+        );
+        // With humn = 5, lgvd = 2 * (5 - dvpt) must equal sllz = 4.
+        assert_eq!(Ok(3), solve("dvpt", "cczh"));
+        assert_eq!(Err(Error::UnknownNotUsed), solve("humn", "sjmn"));
+        assert_eq!(Err(Error::RootIsNumber), solve("humn", "dbpl"));
+    }
+
+    #[test]
+    fn ratios() {
+        assert_eq!(Some(Ratio { num: -2, den: 3 }), Ratio::new(4, -6));
+        assert_eq!(Some(Ratio::int(0)), Ratio::new(0, -5));
+        assert_eq!(None, Ratio::new(i128::MIN, -1));
+        assert_eq!(None, Ratio::new(i128::MIN, i128::MIN));
+        assert_eq!(
+            Err(Error::Overflow),
+            Ratio::new(i128::MIN, 1)
+                .unwrap()
+                .apply(Op::Div, Ratio::int(-1))
+        );
+    }
+
+    #[test]
+    fn solve_errors() {
+        let solve = |s| {
+            let input = parse_input(s);
+            solve(&input, input.symbol("humn"), input.symbol("root"))
+        };
+        // Increasing in humn, which the old binary search couldn't handle.
+        assert_eq!(
+            Ok(7),
+            solve("root: a + b\na: humn + c\nc: 1\nb: 8\nhumn: 0")
+        );
+        assert_eq!(
+            Err(Error::NotInteger(Ratio::new(3, 2).unwrap())),
+            solve("root: a + b\na: humn * c\nc: 2\nb: 3\nhumn: 0")
+        );
+        assert_eq!(
+            Err(Error::NotUnique),
+            solve("root: a + b\na: humn * c\nc: 0\nb: 0\nhumn: 0")
+        );
+        assert_eq!(
+            Err(Error::NoSolution),
+            solve("root: a + b\na: c / humn\nc: 0\nb: 4\nhumn: 0")
+        );
+        assert_eq!(
+            Err(Error::UnknownOnBothSides),
+            solve("root: a + humn\na: humn * humn\nhumn: 0")
+        );
+        // Exactly, humn = 3 works, but the monkeys can't halve 3.
+        assert_eq!(
+            Err(Error::InexactDivision(3, 2)),
+            solve("root: a + b\na: h * c\nh: humn / c\nc: 2\nb: 3\nhumn: 0")
+        );
+    }
+
+    #[test]
+    fn show_sample() {
+        let input = parse_input(SAMPLE);
+        let show = |name| {
+            let tree = expand(input.symbol(name), &[input.symbol("humn")], &input).unwrap();
+            infix(&simplify(tree), &input.names)
+        };
+        assert_eq!("(4 + 2 * (humn - 3)) / 4 + 150", show("root"));
+        assert_eq!("humn - 3", show("ptdq"));
+        assert_eq!("150", show("sjmn"));
+    }
+
+    #[test]
+    fn infix_parentheses() {
+        let input = parse_input("a: b - c\nb: x - y\nc: x - y\nd: e * f\ne: x / y\nf: x / y");
+        let vars = [input.symbol("x"), input.symbol("y")];
+        let show = |name| {
+            infix(
+                &expand(input.symbol(name), &vars, &input).unwrap(),
+                &input.names,
+            )
+        };
+        assert_eq!("x - y - (x - y)", show("a"));
+        assert_eq!("x / y * (x / y)", show("d"));
+        let input = parse_input("a: x - b\nb: -3\nc: b * x\nd: x * e\ne: f / g\nf: -1\ng: 2");
+        let show = |name| {
+            let tree = expand(input.symbol(name), &[input.symbol("x")], &input).unwrap();
+            infix(&fold_constants(tree).unwrap(), &input.names)
+        };
+        assert_eq!("x - (-3)", show("a"));
+        assert_eq!("-3 * x", show("c"));
+        assert_eq!("x * (-1/2)", show("d"));
+    }
+
+    #[test]
+    fn simplify_identities() {
+        let input = parse_input(
+            "a: b * c\nb: d + e\nc: 1\nd: 0\ne: f / g\ng: 1\nh: f * d\ni: k / j\nj: 2\nk: 7",
+        );
+        let f = input.symbol("f");
+        let show = |name| {
+            let tree = expand(input.symbol(name), &[f], &input).unwrap();
+            infix(&simplify(tree), &input.names)
+        };
+        assert_eq!("f", show("a"));
+        assert_eq!("0", show("h"));
+        // Monkeys can't divide 7 by 2, so that's left alone.
+        assert_eq!("7 / 2", show("i"));
+    }
+
+    #[test]
+    fn eval_errors() {
+        let eval = |s| {
+            let input = parse_input(s);
+            eval(input.symbol("root"), &input, &mut SymbolMap::new())
+        };
+        assert_eq!(Ok(3), eval("root: a / b\na: 6\nb: 2"));
+        assert_eq!(
+            Err(Error::InexactDivision(7, 2)),
+            eval("root: a / b\na: 7\nb: 2")
+        );
+        assert_eq!(Err(Error::DivisionByZero), eval("root: a / b\na: 7\nb: 0"));
+        assert_eq!(Err(Error::Overflow), eval("root: a * a\na: 9999999999"));
+        assert_eq!(
+            Err(Error::Overflow),
+            eval("root: a / b\na: -9223372036854775808\nb: -1")
+        );
+        assert!(matches!(
+            eval("root: a + b\na: b + c\nb: a * c\nc: 1"),
+            Err(Error::Cycle(_))
+        ));
+        assert!(matches!(
+            eval("root: a + b\na: 1"),
+            Err(Error::Undefined(_))
+        ));
+    }
+
     #[test]
     fn part1_my_input() {
         let input = parse_input(MY_INPUT);
@@ -5,6 +5,11 @@ const MY_INPUT: &str = include_str!("../../inputs/day-22.txt");
 
 fn main() {
     let input = parse_input(MY_INPUT);
+    if std::env::args().any(|arg| arg == "--trace") {
+        print_trace(&walk(&input, None), &input.map);
+        let cube = Cube::fold(&input.map);
+        print_trace(&walk(&input, Some(&cube)), &input.map);
+    }
     println!("part1: {}", part1(&input));
     println!("part2: {}", part2(&input));
 }
@@ -155,166 +160,308 @@ fn next_pos(r: usize, c: usize, dir: Dir, map: &Map) -> (usize, usize) {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-enum Face {
-    A,
-    B,
-    C,
-    D,
-    E,
-    F,
+type Vec3 = [i32; 3];
+
+fn neg(v: Vec3) -> Vec3 {
+    v.map(|x| -x)
 }
 
-fn pos_to_face(r: usize, c: usize) -> ((usize, usize), Face) {
-    let face_r = r % CUBE_SIZE;
-    let face_c = c % CUBE_SIZE;
-    let face = match (r / CUBE_SIZE, c / CUBE_SIZE) {
-        (0, 1) => Face::A,
-        (0, 2) => Face::B,
-        (1, 1) => Face::C,
-        (2, 0) => Face::D,
-        (2, 1) => Face::E,
-        (3, 0) => Face::F,
-        _ => panic!("bad coords"),
-    };
-    ((face_r, face_c), face)
+fn dot(a: Vec3, b: Vec3) -> i32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Where a face of the net ends up once it's folded into a cube centred on
+/// the origin: which way it faces, and which ways its columns and rows run.
+#[derive(Debug, Clone, Copy)]
+struct Face {
+    block: (usize, usize),
+    normal: Vec3,
+    right: Vec3,
+    down: Vec3,
 }
 
 impl Face {
-    fn to_row_col(&self, r: usize, c: usize) -> (usize, usize) {
-        match self {
-            Face::A => (r, c + CUBE_SIZE),
-            Face::B => (r, c + 2 * CUBE_SIZE),
-            Face::C => (CUBE_SIZE + r, CUBE_SIZE + c),
-            Face::D => (2 * CUBE_SIZE + r, c),
-            Face::E => (2 * CUBE_SIZE + r, CUBE_SIZE + c),
-            Face::F => (3 * CUBE_SIZE + r, c),
+    fn outward(&self, dir: Dir) -> Vec3 {
+        match dir {
+            Dir::R => self.right,
+            Dir::D => self.down,
+            Dir::L => neg(self.right),
+            Dir::U => neg(self.down),
         }
     }
 }
 
-const CUBE_SIZE: usize = 50;
+#[derive(Debug)]
+struct Cube {
+    size: usize,
+    faces: Vec<Face>,
+}
 
-fn next_pos_cube(r: usize, c: usize, dir: Dir, map: &Map) -> ((usize, usize), Dir) {
-    let curr_face = pos_to_face(r, c);
-    match dir {
-        Dir::R => {
-            if c < map.max_col_in_row[r] {
-                ((r, c + 1), dir)
-            } else {
-                let ((r, c), curr_face) = curr_face;
-                match curr_face {
-                    Face::A => (Face::B.to_row_col(r, 0), Dir::R),
-                    Face::B => (Face::E.to_row_col(CUBE_SIZE - 1 - r, CUBE_SIZE - 1), Dir::L),
-                    Face::C => (Face::B.to_row_col(CUBE_SIZE - 1, r), Dir::U),
-                    Face::D => (Face::E.to_row_col(r, 0), Dir::R),
-                    Face::E => (Face::B.to_row_col(CUBE_SIZE - 1 - r, CUBE_SIZE - 1), Dir::L),
-                    Face::F => (Face::E.to_row_col(CUBE_SIZE - 1, r), Dir::U),
+impl Cube {
+    /// Works out the face size from the area of the map, then folds the net
+    /// by walking outwards from the first face, turning each neighbour
+    /// through 90 degrees over the edge it shares with the face before it.
+    fn fold(map: &Map) -> Cube {
+        let area: usize = (0..map.min_col_in_row.len())
+            .map(|r| map.max_col_in_row[r] - map.min_col_in_row[r] + 1)
+            .sum();
+        let size = (1..)
+            .find(|n| 6 * n * n >= area)
+            .filter(|n| 6 * n * n == area)
+            .expect("map area isn't 6 square faces");
+        let in_map = |(br, bc): (usize, usize)| {
+            let r = br * size;
+            let c = bc * size;
+            r < map.min_col_in_row.len()
+                && (map.min_col_in_row[r]..=map.max_col_in_row[r]).contains(&c)
+        };
+        let first = (0, map.min_col_in_row[0] / size);
+        let mut faces = vec![Face {
+            block: first,
+            normal: [0, 0, -1],
+            right: [1, 0, 0],
+            down: [0, 1, 0],
+        }];
+        let mut i = 0;
+        while i < faces.len() {
+            let face = faces[i];
+            let (br, bc) = face.block;
+            let neighbors = [
+                (Some((br, bc + 1)), Dir::R),
+                (Some((br + 1, bc)), Dir::D),
+                (bc.checked_sub(1).map(|bc| (br, bc)), Dir::L),
+                (br.checked_sub(1).map(|br| (br, bc)), Dir::U),
+            ];
+            for (block, dir) in neighbors {
+                let Some(block) = block.filter(|&b| in_map(b)) else {
+                    continue;
+                };
+                if faces.iter().any(|f| f.block == block) {
+                    continue;
                 }
+                let normal = face.outward(dir);
+                let (right, down) = match dir {
+                    Dir::R => (neg(face.normal), face.down),
+                    Dir::D => (face.right, neg(face.normal)),
+                    Dir::L => (face.normal, face.down),
+                    Dir::U => (face.right, face.normal),
+                };
+                faces.push(Face {
+                    block,
+                    normal,
+                    right,
+                    down,
+                });
             }
+            i += 1;
         }
-        Dir::D => {
-            if r < map.max_row_in_col[c] {
-                ((r + 1, c), dir)
-            } else {
-                let ((r, c), curr_face) = curr_face;
-                match curr_face {
-                    Face::A => (Face::C.to_row_col(0, c), Dir::D),
-                    Face::B => (Face::C.to_row_col(c, CUBE_SIZE - 1), Dir::L),
-                    Face::C => (Face::E.to_row_col(0, c), Dir::D),
-                    Face::D => (Face::F.to_row_col(0, c), Dir::D),
-                    Face::E => (Face::F.to_row_col(c, CUBE_SIZE - 1), Dir::L),
-                    Face::F => (Face::B.to_row_col(0, c), Dir::D),
-                }
-            }
+        for (i, face) in faces.iter().enumerate() {
+            assert!(
+                faces[..i].iter().all(|f| f.normal != face.normal),
+                "map doesn't fold into a cube"
+            );
         }
-        Dir::L => {
-            if c > map.min_col_in_row[r] {
-                ((r, c - 1), dir)
-            } else {
-                let ((r, c), curr_face) = curr_face;
-                match curr_face {
-                    Face::A => (Face::D.to_row_col(CUBE_SIZE - 1 - r, 0), Dir::R),
-                    Face::B => (Face::A.to_row_col(r, CUBE_SIZE - 1), Dir::L),
-                    Face::C => (Face::D.to_row_col(0, r), Dir::D),
-                    Face::D => (Face::A.to_row_col(CUBE_SIZE - 1 - r, 0), Dir::R),
-                    Face::E => (Face::D.to_row_col(r, CUBE_SIZE - 1), Dir::L),
-                    Face::F => (Face::A.to_row_col(0, r), Dir::D),
-                }
+        assert_eq!(6, faces.len(), "map doesn't fold into a cube");
+        Cube { size, faces }
+    }
+
+    fn face_index(&self, r: usize, c: usize) -> usize {
+        let block = (r / self.size, c / self.size);
+        self.faces.iter().position(|f| f.block == block).unwrap()
+    }
+
+    /// Moves from the cell at (r, c), which is on the edge of its face, over
+    /// that edge onto the neighbouring face.
+    ///
+    /// Cells are placed in 3D at twice their size, so the cube spans -size
+    /// to size and the centre of every cell has integer coordinates. Going
+    /// over an edge is then a step out to the edge and a step down the
+    /// side of the cube.
+    fn wrap(&self, r: usize, c: usize, dir: Dir) -> ((usize, usize), Dir) {
+        let n = self.size as i32;
+        let from = &self.faces[self.face_index(r, c)];
+        let i = (r % self.size) as i32;
+        let j = (c % self.size) as i32;
+        let outward = from.outward(dir);
+        let p: Vec3 = std::array::from_fn(|k| {
+            n * from.normal[k]
+                + (2 * j + 1 - n) * from.right[k]
+                + (2 * i + 1 - n) * from.down[k]
+                + outward[k]
+                - from.normal[k]
+        });
+        let to = self.faces.iter().find(|f| f.normal == outward).unwrap();
+        let i = (dot(p, to.down) + n - 1) as usize / 2;
+        let j = (dot(p, to.right) + n - 1) as usize / 2;
+        let heading = neg(from.normal);
+        let dir = [Dir::R, Dir::D, Dir::L, Dir::U]
+            .into_iter()
+            .find(|&d| to.outward(d) == heading)
+            .unwrap();
+        let (br, bc) = to.block;
+        ((br * self.size + i, bc * self.size + j), dir)
+    }
+}
+
+fn stays_on_map(r: usize, c: usize, dir: Dir, map: &Map) -> bool {
+    match dir {
+        Dir::R => c < map.max_col_in_row[r],
+        Dir::D => r < map.max_row_in_col[c],
+        Dir::L => c > map.min_col_in_row[r],
+        Dir::U => r > map.min_row_in_col[c],
+    }
+}
+
+fn next_pos_cube(r: usize, c: usize, dir: Dir, map: &Map, cube: &Cube) -> ((usize, usize), Dir) {
+    if stays_on_map(r, c, dir, map) {
+        (next_pos(r, c, dir, map), dir)
+    } else {
+        cube.wrap(r, c, dir)
+    }
+}
+
+type Pos = ((usize, usize), Dir);
+
+#[derive(Debug, PartialEq, Eq)]
+enum Event {
+    /// The walk was stopped by the wall at `wall`.
+    Wall {
+        inst: usize,
+        at: Pos,
+        wall: (usize, usize),
+    },
+    /// The walk went off the edge of the map. In cube mode `faces` are the
+    /// indices in [`Cube::faces`] of the faces either side of the edge.
+    Wrap {
+        inst: usize,
+        from: Pos,
+        to: Pos,
+        faces: Option<(usize, usize)>,
+    },
+}
+
+impl std::fmt::Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Event::Wall { inst, at, wall } => {
+                write!(
+                    f,
+                    "{inst}: hit wall at {wall:?} from {:?} facing {:?}",
+                    at.0, at.1
+                )
             }
-        }
-        Dir::U => {
-            if r > map.min_row_in_col[c] {
-                ((r - 1, c), dir)
-            } else {
-                let ((r, c), curr_face) = curr_face;
-                match curr_face {
-                    Face::A => (Face::F.to_row_col(c, 0), Dir::R),
-                    Face::B => (Face::F.to_row_col(CUBE_SIZE - 1, c), Dir::U),
-                    Face::C => (Face::A.to_row_col(CUBE_SIZE - 1, c), Dir::U),
-                    Face::D => (Face::C.to_row_col(c, 0), Dir::R),
-                    Face::E => (Face::C.to_row_col(CUBE_SIZE - 1, c), Dir::U),
-                    Face::F => (Face::D.to_row_col(CUBE_SIZE - 1, c), Dir::U),
+            Event::Wrap {
+                inst,
+                from,
+                to,
+                faces,
+            } => {
+                write!(
+                    f,
+                    "{inst}: wrapped from {:?} facing {:?} to {:?} facing {:?}",
+                    from.0, from.1, to.0, to.1
+                )?;
+                if let Some((a, b)) = faces {
+                    write!(f, " (face {a} to face {b})")?;
                 }
+                Ok(())
             }
         }
     }
 }
 
-fn part1(input: &Input) -> usize {
-    let mut r = 0;
-    let mut c = input.map.min_col_in_row[0];
-    while input.map.walls.contains(&(r, c)) {
-        c += 1;
+/// Everything that happened while following the instructions. Positions are
+/// 0-based, and instructions are numbered by their index in the input.
+#[derive(Debug)]
+struct Trace {
+    /// Every position visited, including turning on the spot.
+    steps: Vec<Pos>,
+    events: Vec<Event>,
+}
+
+impl Trace {
+    fn password(&self) -> usize {
+        let ((r, c), dir) = *self.steps.last().unwrap();
+        1000 * (r + 1) + 4 * (c + 1) + dir as usize
     }
-    let mut dir = Dir::R;
-    for inst in &input.instructions {
-        match inst {
-            Instruction::Walk(count) => {
-                for _ in 0..*count {
-                    let (next_r, next_c) = next_pos(r, c, dir, &input.map);
-                    if input.map.walls.contains(&(next_r, next_c)) {
-                        break;
-                    }
-                    r = next_r;
-                    c = next_c;
-                }
-            }
-            Instruction::Turn(turn) => {
-                dir = match (turn, dir) {
-                    (Turn::L, Dir::R) => Dir::U,
-                    (Turn::L, Dir::D) => Dir::R,
-                    (Turn::L, Dir::L) => Dir::D,
-                    (Turn::L, Dir::U) => Dir::L,
-                    (Turn::R, Dir::R) => Dir::D,
-                    (Turn::R, Dir::D) => Dir::L,
-                    (Turn::R, Dir::L) => Dir::U,
-                    (Turn::R, Dir::U) => Dir::R,
-                };
-            }
+
+    /// Draws the map with the last direction faced in each visited cell,
+    /// like the puzzle does.
+    fn render(&self, map: &Map) -> String {
+        let mut rows: Vec<Vec<char>> = (0..map.min_col_in_row.len())
+            .map(|r| {
+                (0..=map.max_col_in_row[r])
+                    .map(|c| {
+                        if c < map.min_col_in_row[r] {
+                            ' '
+                        } else if map.walls.contains(&(r, c)) {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        for &((r, c), dir) in &self.steps {
+            rows[r][c] = match dir {
+                Dir::R => '>',
+                Dir::D => 'v',
+                Dir::L => '<',
+                Dir::U => '^',
+            };
         }
+        rows.into_iter()
+            .map(|row| row.into_iter().chain(['\n']).collect::<String>())
+            .collect()
     }
-    1000 * (r + 1) + 4 * (c + 1) + dir as usize
 }
 
-fn part2(input: &Input) -> usize {
+/// Follows the instructions from the start, wrapping around the flat map, or
+/// around the cube if there is one.
+fn walk(input: &Input, cube: Option<&Cube>) -> Trace {
+    let map = &input.map;
     let mut r = 0;
-    let mut c = input.map.min_col_in_row[0];
-    while input.map.walls.contains(&(r, c)) {
+    let mut c = map.min_col_in_row[0];
+    while map.walls.contains(&(r, c)) {
         c += 1;
     }
     let mut dir = Dir::R;
-    for inst in &input.instructions {
+    let mut trace = Trace {
+        steps: vec![((r, c), dir)],
+        events: vec![],
+    };
+    for (inst_index, inst) in input.instructions.iter().enumerate() {
         match inst {
             Instruction::Walk(count) => {
                 for _ in 0..*count {
-                    let ((next_r, next_c), next_dir) = next_pos_cube(r, c, dir, &input.map);
-                    if input.map.walls.contains(&(next_r, next_c)) {
+                    let wraps = !stays_on_map(r, c, dir, map);
+                    let ((next_r, next_c), next_dir) = match cube {
+                        Some(cube) => next_pos_cube(r, c, dir, map, cube),
+                        None => (next_pos(r, c, dir, map), dir),
+                    };
+                    if map.walls.contains(&(next_r, next_c)) {
+                        trace.events.push(Event::Wall {
+                            inst: inst_index,
+                            at: ((r, c), dir),
+                            wall: (next_r, next_c),
+                        });
                         break;
                     }
+                    if wraps {
+                        let faces = cube
+                            .map(|cube| (cube.face_index(r, c), cube.face_index(next_r, next_c)));
+                        trace.events.push(Event::Wrap {
+                            inst: inst_index,
+                            from: ((r, c), dir),
+                            to: ((next_r, next_c), next_dir),
+                            faces,
+                        });
+                    }
                     r = next_r;
                     c = next_c;
                     dir = next_dir;
+                    trace.steps.push(((r, c), dir));
                 }
             }
             Instruction::Turn(turn) => {
@@ -328,10 +475,27 @@ fn part2(input: &Input) -> usize {
                     (Turn::R, Dir::L) => Dir::U,
                     (Turn::R, Dir::U) => Dir::R,
                 };
+                trace.steps.push(((r, c), dir));
             }
         }
     }
-    1000 * (r + 1) + 4 * (c + 1) + dir as usize
+    trace
+}
+
+fn print_trace(trace: &Trace, map: &Map) {
+    print!("{}", trace.render(map));
+    for event in &trace.events {
+        println!("{event}");
+    }
+}
+
+fn part1(input: &Input) -> usize {
+    walk(input, None).password()
+}
+
+fn part2(input: &Input) -> usize {
+    let cube = Cube::fold(&input.map);
+    walk(input, Some(&cube)).password()
 }
 
 #[cfg(test)]
@@ -359,6 +523,130 @@ mod tests {
         assert_eq!(part1(&input), 6032);
     }
 
+    #[test]
+    fn part2_sample() {
+        let input = parse_input(SAMPLE);
+        assert_eq!(part2(&input), 5031);
+    }
+
+    #[test]
+    fn part1_trace_sample() {
+        let input = parse_input(SAMPLE);
+        let trace = walk(&input, None);
+        let expected = "        >>v#
+        .#v.
+        #.v.
+        ..v.
+...#...v..v#
+>>>v...>#.>>
+..#v...#....
+...>>>>v..#.
+        ...#....
+        .....#..
+        .#......
+        ......#.
+";
+        assert_eq!(expected, trace.render(&input.map));
+        assert_eq!(
+            Event::Wrap {
+                inst: 4,
+                from: ((5, 11), Dir::R),
+                to: ((5, 0), Dir::R),
+                faces: None,
+            },
+            trace.events[1]
+        );
+        assert_eq!(6, trace.events.len());
+    }
+
+    #[test]
+    fn part2_trace_sample() {
+        let input = parse_input(SAMPLE);
+        let cube = Cube::fold(&input.map);
+        let trace = walk(&input, Some(&cube));
+        let expected = "        >>v#
+        .#v.
+        #.v.
+        ..v.
+...#..^...v#
+.>>>>>^.#.>>
+.^#....#....
+.^........#.
+        ...#..v.
+        .....#v.
+        .#v<<<<.
+        ..v...#.
+";
+        assert_eq!(expected, trace.render(&input.map));
+        assert_eq!(
+            Event::Wrap {
+                inst: 4,
+                from: ((5, 11), Dir::R),
+                to: ((8, 14), Dir::D),
+                faces: Some((1, 4)),
+            },
+            trace.events[1]
+        );
+        assert_eq!(
+            "12: hit wall at (2, 8) from (4, 6) facing U",
+            trace.events.last().unwrap().to_string()
+        );
+    }
+
+    // The 11 nets of a cube, one character per face.
+    const NETS: &[&str] = &[
+        "#\n####\n#",
+        "#\n####\n #",
+        "#\n####\n  #",
+        "#\n####\n   #",
+        " #\n####\n #",
+        " #\n####\n  #",
+        "##\n ###\n #",
+        "##\n ###\n  #",
+        "##\n ###\n   #",
+        "##\n ##\n  ##",
+        "###\n  ###",
+    ];
+
+    fn scale_net(net: &str, size: usize) -> Map {
+        let mut s = String::new();
+        for line in net.lines() {
+            let row: String = line
+                .chars()
+                .flat_map(|c| std::iter::repeat_n(if c == '#' { '.' } else { ' ' }, size))
+                .collect();
+            for _ in 0..size {
+                s.push_str(&row);
+                s.push('\n');
+            }
+        }
+        parse_map(&s)
+    }
+
+    #[test]
+    fn every_net_folds() {
+        for net in NETS {
+            for size in 1..=3 {
+                let map = scale_net(net, size);
+                let cube = Cube::fold(&map);
+                assert_eq!(size, cube.size);
+                for r in 0..map.min_col_in_row.len() {
+                    for c in map.min_col_in_row[r]..=map.max_col_in_row[r] {
+                        for dir in [Dir::R, Dir::D, Dir::L, Dir::U] {
+                            // Going straight all the way round the cube
+                            // comes back to the start.
+                            let mut pos = ((r, c), dir);
+                            for _ in 0..4 * size {
+                                pos = next_pos_cube(pos.0 .0, pos.0 .1, pos.1, &map, &cube);
+                            }
+                            assert_eq!(((r, c), dir), pos, "{net:?} size {size}");
+                        }
+                    }
+                }
+            }
+        }
+    }
+
     #[test]
     fn part1_my_input() {
         let input = parse_input(MY_INPUT);
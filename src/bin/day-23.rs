@@ -1,12 +1,16 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
 
+use aoc_2022::record::Recorder;
+
 const MY_INPUT: &str = include_str!("../../inputs/day-23.txt");
 
 fn main() {
     let input = parse_input(MY_INPUT);
+    let mut recorder = Recorder::from_args();
     println!("part1: {}", part1(&input));
-    println!("part2: {}", part2(&input));
+    println!("part2: {}", part2(&input, &mut recorder));
+    recorder.finish().unwrap();
 }
 
 type Input = HashSet<(i32, i32)>;
@@ -124,9 +128,13 @@ fn part1(input: &Input) -> i32 {
     (max_row - min_row + 1) * (max_col - min_col + 1) - positions.len() as i32
 }
 
-fn part2(input: &Input) -> usize {
+fn part2(input: &Input, recorder: &mut Recorder) -> usize {
     let mut positions = input.clone();
     for round in 0.. {
+        recorder.push_points(
+            positions.iter().map(|&(r, c)| ((r as i64, c as i64), '#')),
+            '.',
+        );
         let mut next_positions = HashSet::new();
         // Maps proposed spot back to the elves who want to move there.
         let mut proposals: HashMap<(i32, i32), Vec<(i32, i32)>> = HashMap::new();
@@ -198,7 +206,7 @@ mod tests {
     #[test]
     fn part2_sample() {
         let input = parse_input(SAMPLE);
-        assert_eq!(part2(&input), 20);
+        assert_eq!(part2(&input, &mut Recorder::disabled()), 20);
     }
 
     #[test]
@@ -210,6 +218,6 @@ mod tests {
     #[test]
     fn part2_my_input() {
         let input = parse_input(MY_INPUT);
-        assert_eq!(part2(&input), 923);
+        assert_eq!(part2(&input, &mut Recorder::disabled()), 923);
     }
 }
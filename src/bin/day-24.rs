@@ -1,3 +1,4 @@
+use aoc_2022::record::Recorder;
 use aoc_2022::*;
 use std::collections::HashSet;
 
@@ -5,8 +6,10 @@ const MY_INPUT: &str = include_str!("../../inputs/day-24.txt");
 
 fn main() {
     let input = parse_input(MY_INPUT);
+    let mut recorder = Recorder::from_args();
     println!("part1: {}", part1(&input));
-    println!("part2: {}", part2(&input));
+    println!("part2: {}", part2(&input, &mut recorder));
+    recorder.finish().unwrap();
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -37,12 +40,12 @@ fn parse_input(s: &str) -> Input {
     rows.into()
 }
 
-fn blizzard_positions(input: &Input, turn: usize) -> Vec<Vec<bool>> {
-    let mut ps = vec![vec![false; input.num_cols()]; input.num_rows()];
-    for ((r, c), dir) in input.enumerated_elems() {
-        let (future_r, future_c) = match dir {
-            None => continue,
-            Some(Dir::U) => {
+/// Where each blizzard is after `turn` turns, along with its direction.
+fn moved_blizzards(input: &Input, turn: usize) -> impl Iterator<Item = ((usize, usize), &Dir)> {
+    input.enumerated_elems().filter_map(move |((r, c), dir)| {
+        let dir = dir.as_ref()?;
+        let future = match dir {
+            Dir::U => {
                 let turn = turn % (input.num_rows() - 2);
                 // going up 1 is like going down (input.num_rows() - 2 - 1)
                 // going up 2 is like going down (input.num_rows() - 2 - 2)
@@ -50,34 +53,104 @@ fn blizzard_positions(input: &Input, turn: usize) -> Vec<Vec<bool>> {
                 let new_r = (r - 1 + down) % (input.num_rows() - 2) + 1;
                 (new_r, c)
             }
-            Some(Dir::D) => ((r - 1 + turn) % (input.num_rows() - 2) + 1, c),
-            Some(Dir::L) => {
+            Dir::D => ((r - 1 + turn) % (input.num_rows() - 2) + 1, c),
+            Dir::L => {
                 let turn = turn % (input.num_cols() - 2);
                 let right = input.num_cols() - 2 - turn;
                 let new_c = (c - 1 + right) % (input.num_cols() - 2) + 1;
                 (r, new_c)
             }
-            Some(Dir::R) => (r, (c - 1 + turn) % (input.num_cols() - 2) + 1),
+            Dir::R => (r, (c - 1 + turn) % (input.num_cols() - 2) + 1),
         };
-        ps[future_r][future_c] = true;
+        Some((future, dir))
+    })
+}
+
+fn blizzard_positions(input: &Input, turn: usize) -> Vec<Vec<bool>> {
+    let mut ps = vec![vec![false; input.num_cols()]; input.num_rows()];
+    for ((r, c), _) in moved_blizzards(input, turn) {
+        ps[r][c] = true;
     }
     ps
 }
 
+// Draws the valley like the puzzle does, with the expedition's possible
+// positions marked E.
+fn render_valley(input: &Input, turn: usize, positions: &[(i32, i32)]) -> String {
+    let mut cells: Vec<Vec<char>> = (0..input.num_rows())
+        .map(|r| {
+            (0..input.num_cols())
+                .map(|c| {
+                    let is_wall =
+                        r == 0 || r == input.num_rows() - 1 || c == 0 || c == input.num_cols() - 1;
+                    let is_gap =
+                        (r, c) == (0, 1) || (r, c) == (input.num_rows() - 1, input.num_cols() - 2);
+                    if is_wall && !is_gap {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    for ((r, c), dir) in moved_blizzards(input, turn) {
+        cells[r][c] = match (cells[r][c], dir) {
+            ('.', Dir::U) => '^',
+            ('.', Dir::D) => 'v',
+            ('.', Dir::L) => '<',
+            ('.', Dir::R) => '>',
+            ('<' | '>' | '^' | 'v', _) => '2',
+            (n, _) => char::from_digit(n.to_digit(10).unwrap() + 1, 10).unwrap_or('*'),
+        };
+    }
+    for &(r, c) in positions {
+        cells[r as usize][c as usize] = 'E';
+    }
+    cells
+        .into_iter()
+        .map(|row| row.into_iter().chain(['\n']).collect::<String>())
+        .collect()
+}
+
+// The blizzards are back where they started every lcm(width, height) turns,
+// so that's the only number of distinct states there are to compute.
+fn blizzard_cycle(input: &Input) -> Vec<Vec<Vec<bool>>> {
+    let period = math::lcm(input.num_rows() - 2, input.num_cols() - 2).unwrap();
+    (0..period)
+        .map(|turn| blizzard_positions(input, turn))
+        .collect()
+}
+
 const DELTAS: &[(i32, i32)] = &[(0, 0), (-1, 0), (1, 0), (0, 1), (0, -1)];
 
 fn part1(input: &Input) -> usize {
     let start = (0, 1);
     let goal = ((input.num_rows() - 1) as i32, (input.num_cols() - 2) as i32);
-    reach_goal(input, 0, start, goal)
+    let blizzard_cycle = blizzard_cycle(input);
+    reach_goal(
+        input,
+        &blizzard_cycle,
+        0,
+        start,
+        goal,
+        &mut Recorder::disabled(),
+    )
 }
 
-fn reach_goal(input: &Input, start_time: usize, start: (i32, i32), goal: (i32, i32)) -> usize {
+fn reach_goal(
+    input: &Input,
+    blizzard_cycle: &[Vec<Vec<bool>>],
+    start_time: usize,
+    start: (i32, i32),
+    goal: (i32, i32),
+    recorder: &mut Recorder,
+) -> usize {
     let mut positions = vec![start];
     let end = ((input.num_rows() - 1) as i32, (input.num_cols() - 2) as i32);
     for t in (start_time + 1).. {
         let mut next_positions = HashSet::new();
-        let blizzards = blizzard_positions(input, t);
+        let blizzards = &blizzard_cycle[t % blizzard_cycle.len()];
         for (r, c) in positions {
             let deltas = if (r, c) == (0, 1) {
                 &[(0, 0), (1, 0)]
@@ -89,6 +162,7 @@ fn reach_goal(input: &Input, start_time: usize, start: (i32, i32), goal: (i32, i
             for &(dr, dc) in deltas {
                 let n @ (nr, nc) = (r + dr, c + dc);
                 if n == goal {
+                    recorder.push(|| render_valley(input, t, &[goal]));
                     return t;
                 }
                 if (nr == 0 && (nr, nc) != start)
@@ -103,16 +177,18 @@ fn reach_goal(input: &Input, start_time: usize, start: (i32, i32), goal: (i32, i
             }
         }
         positions = next_positions.into_iter().collect();
+        recorder.push(|| render_valley(input, t, &positions));
     }
     panic!("unreachable");
 }
 
-fn part2(input: &Input) -> usize {
+fn part2(input: &Input, recorder: &mut Recorder) -> usize {
     let start = (0, 1);
     let goal = ((input.num_rows() - 1) as i32, (input.num_cols() - 2) as i32);
-    let a = reach_goal(input, 0, start, goal);
-    let b = reach_goal(input, a, goal, start);
-    reach_goal(input, b, start, goal)
+    let blizzard_cycle = blizzard_cycle(input);
+    let a = reach_goal(input, &blizzard_cycle, 0, start, goal, recorder);
+    let b = reach_goal(input, &blizzard_cycle, a, goal, start, recorder);
+    reach_goal(input, &blizzard_cycle, b, start, goal, recorder)
 }
 
 #[cfg(test)]
@@ -135,7 +211,7 @@ mod tests {
     #[test]
     fn part2_sample() {
         let input = parse_input(SAMPLE);
-        assert_eq!(part2(&input), 54);
+        assert_eq!(part2(&input, &mut Recorder::disabled()), 54);
     }
 
     #[test]
@@ -147,6 +223,6 @@ mod tests {
     #[test]
     fn part2_my_input() {
         let input = parse_input(MY_INPUT);
-        assert_eq!(part2(&input), 728);
+        assert_eq!(part2(&input, &mut Recorder::disabled()), 728);
     }
 }
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::ops::{Index, IndexMut};
+
+/// A dense id for a name stored in an [`Interner`]. Ids are handed out in
+/// the order names are first seen, starting at 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Maps names to [`Symbol`]s and back, so puzzles keyed by name can work
+/// with small copyable ids instead of borrowing from the input.
+#[derive(Debug, Default, Clone)]
+pub struct Interner {
+    names: Vec<Box<str>>,
+    ids: HashMap<Box<str>, Symbol>,
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        Interner::default()
+    }
+
+    /// Returns the id for `name`, assigning the next one if it's new.
+    pub fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = Symbol(u32::try_from(self.names.len()).expect("too many symbols"));
+        self.names.push(name.into());
+        self.ids.insert(name.into(), id);
+        id
+    }
+
+    /// Returns the id for `name` if it has already been interned.
+    pub fn get(&self, name: &str) -> Option<Symbol> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn resolve(&self, id: Symbol) -> &str {
+        &self.names[id.index()]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Symbol, &str)> {
+        self.names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (Symbol(i as u32), &**name))
+    }
+}
+
+/// A side table holding at most one value per [`Symbol`], stored by index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolMap<T> {
+    slots: Vec<Option<T>>,
+}
+
+impl<T> Default for SymbolMap<T> {
+    fn default() -> Self {
+        SymbolMap { slots: vec![] }
+    }
+}
+
+impl<T> SymbolMap<T> {
+    pub fn new() -> SymbolMap<T> {
+        SymbolMap::default()
+    }
+
+    /// Stores `val` for `id`, returning the value it replaced.
+    pub fn insert(&mut self, id: Symbol, val: T) -> Option<T> {
+        if id.index() >= self.slots.len() {
+            self.slots.resize_with(id.index() + 1, || None);
+        }
+        self.slots[id.index()].replace(val)
+    }
+
+    pub fn remove(&mut self, id: Symbol) -> Option<T> {
+        self.slots.get_mut(id.index())?.take()
+    }
+
+    pub fn get(&self, id: Symbol) -> Option<&T> {
+        self.slots.get(id.index())?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, id: Symbol) -> Option<&mut T> {
+        self.slots.get_mut(id.index())?.as_mut()
+    }
+
+    pub fn contains(&self, id: Symbol) -> bool {
+        self.get(id).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Symbol, &T)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(i, slot)| Some((Symbol(i as u32), slot.as_ref()?)))
+    }
+}
+
+impl<T> Index<Symbol> for SymbolMap<T> {
+    type Output = T;
+
+    fn index(&self, id: Symbol) -> &Self::Output {
+        self.get(id).expect("no entry for symbol")
+    }
+}
+
+impl<T> IndexMut<Symbol> for SymbolMap<T> {
+    fn index_mut(&mut self, id: Symbol) -> &mut Self::Output {
+        self.get_mut(id).expect("no entry for symbol")
+    }
+}
+
+impl<T> FromIterator<(Symbol, T)> for SymbolMap<T> {
+    fn from_iter<I: IntoIterator<Item = (Symbol, T)>>(iter: I) -> Self {
+        let mut map = SymbolMap::new();
+        for (id, val) in iter {
+            map.insert(id, val);
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intern_and_resolve() {
+        let mut names = Interner::new();
+        let aa = names.intern("AA");
+        let bb = names.intern("BB");
+        assert_eq!(aa, names.intern("AA"));
+        assert_eq!(0, aa.index());
+        assert_eq!(1, bb.index());
+        assert_eq!("BB", names.resolve(bb));
+        assert_eq!(Some(bb), names.get("BB"));
+        assert_eq!(None, names.get("CC"));
+        assert_eq!(2, names.len());
+        let all: Vec<_> = names.iter().collect();
+        assert_eq!(vec![(aa, "AA"), (bb, "BB")], all);
+    }
+
+    #[test]
+    fn symbol_map() {
+        let mut names = Interner::new();
+        let a = names.intern("a");
+        let b = names.intern("b");
+        let c = names.intern("c");
+        let mut map = SymbolMap::new();
+        assert_eq!(None, map.insert(c, 3));
+        assert_eq!(None, map.insert(a, 1));
+        assert_eq!(Some(1), map.insert(a, 10));
+        assert!(!map.contains(b));
+        assert_eq!(None, map.get(b));
+        map[c] += 1;
+        assert_eq!(4, map[c]);
+        let entries: Vec<_> = map.iter().map(|(id, &v)| (id, v)).collect();
+        assert_eq!(vec![(a, 10), (c, 4)], entries);
+        assert_eq!(Some(10), map.remove(a));
+        assert!(!map.contains(a));
+    }
+}
@@ -5,6 +5,16 @@ use std::{
 
 use num_complex::Complex;
 
+mod intern;
+pub mod math;
+pub mod ocr;
+mod range_set;
+pub mod record;
+pub mod search;
+
+pub use intern::{Interner, Symbol, SymbolMap};
+pub use range_set::{Discrete, RangeSet};
+
 pub struct Grid<T> {
     elems: Vec<T>,
     num_rows: usize,
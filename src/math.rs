@@ -0,0 +1,487 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Debug};
+use std::ops::{Add, Div, Mul, Rem, Sub};
+
+/// The primitive integer operations the helpers in this module are written
+/// against. Implemented for every signed and unsigned primitive width.
+pub trait Integer:
+    Copy
+    + Ord
+    + Debug
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const TWO: Self;
+
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+
+    fn checked_neg(self) -> Option<Self>;
+
+    fn checked_rem(self, rhs: Self) -> Option<Self>;
+
+    fn rem_euclid(self, rhs: Self) -> Self;
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const TWO: Self = 2;
+
+                fn checked_mul(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, rhs)
+                }
+
+                fn checked_neg(self) -> Option<Self> {
+                    <$t>::checked_neg(self)
+                }
+
+                fn checked_rem(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_rem(self, rhs)
+                }
+
+                fn rem_euclid(self, rhs: Self) -> Self {
+                    <$t>::rem_euclid(self, rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// The greatest common divisor of `a` and `b`, always non-negative, or
+/// `None` if it doesn't fit in a `T`. That only happens for a signed type's
+/// `MIN` paired with 0 or itself. `gcd(0, 0)` is 0.
+pub fn gcd<T: Integer>(mut a: T, mut b: T) -> Option<T> {
+    while b != T::ZERO {
+        // Only `MIN % -1` overflows, and it's really 0.
+        (a, b) = (b, a.checked_rem(b).unwrap_or(T::ZERO));
+    }
+    if a < T::ZERO {
+        a.checked_neg()
+    } else {
+        Some(a)
+    }
+}
+
+/// The least common multiple of `a` and `b`, or `None` if it doesn't fit in
+/// a `T`.
+pub fn lcm<T: Integer>(a: T, b: T) -> Option<T> {
+    if a == T::ZERO || b == T::ZERO {
+        return Some(T::ZERO);
+    }
+    let a = gcd(a, T::ZERO)?;
+    let b = gcd(b, T::ZERO)?;
+    (a / gcd(a, b)?).checked_mul(b)
+}
+
+/// The greatest common divisor of every value, or `None` if it overflows.
+/// The gcd of no values is 0.
+pub fn gcd_all<T: Integer>(vals: impl IntoIterator<Item = T>) -> Option<T> {
+    vals.into_iter().try_fold(T::ZERO, gcd)
+}
+
+/// The least common multiple of every value, or `None` if it overflows.
+/// The lcm of no values is 1.
+pub fn lcm_all<T: Integer>(vals: impl IntoIterator<Item = T>) -> Option<T> {
+    vals.into_iter().try_fold(T::ONE, |acc, val| lcm(acc, val))
+}
+
+// Both arguments must already be in 0..m.
+fn add_mod<T: Integer>(a: T, b: T, m: T) -> T {
+    if a >= m - b {
+        a - (m - b)
+    } else {
+        a + b
+    }
+}
+
+// Both arguments must already be in 0..m.
+fn sub_mod<T: Integer>(a: T, b: T, m: T) -> T {
+    if a >= b {
+        a - b
+    } else {
+        m - (b - a)
+    }
+}
+
+// Both arguments must already be in 0..m. Never overflows, since every
+// intermediate value stays below m.
+fn mul_mod<T: Integer>(mut a: T, mut b: T, m: T) -> T {
+    let mut product = T::ZERO;
+    while b > T::ZERO {
+        if b % T::TWO == T::ONE {
+            product = add_mod(product, a, m);
+        }
+        a = add_mod(a, a, m);
+        b = b / T::TWO;
+    }
+    product
+}
+
+/// `a * b` reduced into `0..m`, computed without overflowing `T`. Returns
+/// `None` if `m` isn't positive.
+pub fn checked_mul_mod<T: Integer>(a: T, b: T, m: T) -> Option<T> {
+    if m <= T::ZERO {
+        return None;
+    }
+    Some(mul_mod(a.rem_euclid(m), b.rem_euclid(m), m))
+}
+
+/// `base` raised to `exp`, reduced into `0..m`, computed without
+/// overflowing `T`. Returns `None` if `m` isn't positive or `exp` is
+/// negative.
+pub fn checked_pow_mod<T: Integer>(base: T, mut exp: T, m: T) -> Option<T> {
+    if m <= T::ZERO || exp < T::ZERO {
+        return None;
+    }
+    let mut base = base.rem_euclid(m);
+    let mut result = T::ONE % m;
+    while exp > T::ZERO {
+        if exp % T::TWO == T::ONE {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exp = exp / T::TWO;
+    }
+    Some(result)
+}
+
+/// The `x` in `0..m` with `a * x ≡ 1 (mod m)`, or `None` if `a` and `m`
+/// aren't coprime or `m` isn't positive.
+pub fn mod_inverse<T: Integer>(a: T, m: T) -> Option<T> {
+    if m <= T::ZERO {
+        return None;
+    }
+    // Extended Euclid, keeping the Bézout coefficient reduced mod m so that
+    // it works for unsigned types too.
+    let (mut old_r, mut r) = (a.rem_euclid(m), m);
+    let (mut old_s, mut s) = (T::ONE % m, T::ZERO);
+    while r != T::ZERO {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, sub_mod(old_s, mul_mod(q % m, s, m), m));
+    }
+    (old_r == T::ONE).then_some(old_s)
+}
+
+/// Solves the system `x ≡ r (mod m)` for every `(r, m)` pair using the
+/// Chinese remainder theorem. The moduli needn't be coprime.
+///
+/// Returns `(x, l)` where `l` is the lcm of the moduli and `x` is the unique
+/// solution in `0..l`, or `None` if the congruences are inconsistent, a
+/// modulus isn't positive, or `l` doesn't fit in a `T`.
+pub fn crt<T: Integer>(congruences: impl IntoIterator<Item = (T, T)>) -> Option<(T, T)> {
+    let mut x = T::ZERO;
+    let mut l = T::ONE;
+    for (r2, m2) in congruences {
+        if m2 <= T::ZERO {
+            return None;
+        }
+        // Find k such that x + l*k ≡ r2 (mod m2).
+        let g = gcd(l, m2)?;
+        let diff = sub_mod(r2.rem_euclid(m2), x % m2, m2);
+        if diff % g != T::ZERO {
+            return None;
+        }
+        let m2_g = m2 / g;
+        let inv = mod_inverse(l / g, m2_g)?;
+        let k = mul_mod((diff / g) % m2_g, inv, m2_g);
+        let new_l = (l / g).checked_mul(m2)?;
+        // l * k < l * m2 / g == new_l, so this can't overflow.
+        x = add_mod(x, l * k, new_l);
+        l = new_l;
+    }
+    Some((x, l))
+}
+
+/// An unsigned integer of any size, for when a primitive would overflow.
+/// Only the operations the puzzles need are provided.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigUint {
+    // Base 2^32, least significant first, with no trailing zeros.
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    fn normalized(mut limbs: Vec<u32>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        BigUint { limbs }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// The number of bits needed to write the value, 0 for zero.
+    pub fn bits(&self) -> usize {
+        match self.limbs.last() {
+            None => 0,
+            Some(top) => 32 * self.limbs.len() - top.leading_zeros() as usize,
+        }
+    }
+
+    fn bit(&self, i: usize) -> bool {
+        self.limbs
+            .get(i / 32)
+            .is_some_and(|limb| limb >> (i % 32) & 1 == 1)
+    }
+
+    /// `self - rhs`, or `None` if it would be negative.
+    pub fn checked_sub(&self, rhs: &BigUint) -> Option<BigUint> {
+        if *self < *rhs {
+            return None;
+        }
+        let mut limbs = Vec::with_capacity(self.limbs.len());
+        let mut borrow = 0;
+        for (i, &limb) in self.limbs.iter().enumerate() {
+            let r = *rhs.limbs.get(i).unwrap_or(&0) as i64;
+            let mut diff = limb as i64 - r - borrow;
+            borrow = 0;
+            if diff < 0 {
+                diff += 1 << 32;
+                borrow = 1;
+            }
+            limbs.push(diff as u32);
+        }
+        Some(BigUint::normalized(limbs))
+    }
+
+    /// The quotient and remainder of `self / rhs`, or `None` if `rhs` is
+    /// zero.
+    pub fn checked_div_rem(&self, rhs: &BigUint) -> Option<(BigUint, BigUint)> {
+        if rhs.is_zero() {
+            return None;
+        }
+        if let [d] = rhs.limbs[..] {
+            let (q, r) = self.div_rem_u64(d as u64);
+            return Some((q, BigUint::from(r)));
+        }
+        // Schoolbook long division, a bit at a time.
+        let mut quotient = vec![0; self.limbs.len()];
+        let mut rem = BigUint::default();
+        for i in (0..self.bits()).rev() {
+            rem = &rem + &rem;
+            if self.bit(i) {
+                rem = &rem + &BigUint::from(1);
+            }
+            if rem >= *rhs {
+                rem = rem.checked_sub(rhs).unwrap();
+                quotient[i / 32] |= 1 << (i % 32);
+            }
+        }
+        Some((BigUint::normalized(quotient), rem))
+    }
+
+    /// The quotient and remainder of `self / d`. Panics if `d` is zero.
+    pub fn div_rem_u64(&self, d: u64) -> (BigUint, u64) {
+        assert!(d != 0, "division by zero");
+        let d = d as u128;
+        let mut quotient = vec![0; self.limbs.len()];
+        let mut rem = 0u128;
+        for (i, &limb) in self.limbs.iter().enumerate().rev() {
+            // rem < d, so each digit of the quotient fits in a limb.
+            let cur = rem << 32 | limb as u128;
+            quotient[i] = (cur / d) as u32;
+            rem = cur % d;
+        }
+        (BigUint::normalized(quotient), rem as u64)
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(n: u64) -> Self {
+        BigUint::normalized(vec![n as u32, (n >> 32) as u32])
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, rhs: &BigUint) -> BigUint {
+        let len = self.limbs.len().max(rhs.limbs.len());
+        let mut limbs = Vec::with_capacity(len + 1);
+        let mut carry = 0u64;
+        for i in 0..len {
+            let sum = *self.limbs.get(i).unwrap_or(&0) as u64
+                + *rhs.limbs.get(i).unwrap_or(&0) as u64
+                + carry;
+            limbs.push(sum as u32);
+            carry = sum >> 32;
+        }
+        limbs.push(carry as u32);
+        BigUint::normalized(limbs)
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, rhs: &BigUint) -> BigUint {
+        let mut limbs = vec![0u32; self.limbs.len() + rhs.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in rhs.limbs.iter().enumerate() {
+                let cur = limbs[i + j] as u64 + a as u64 * b as u64 + carry;
+                limbs[i + j] = cur as u32;
+                carry = cur >> 32;
+            }
+            limbs[i + rhs.limbs.len()] = carry as u32;
+        }
+        BigUint::normalized(limbs)
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const CHUNK: u64 = 1_000_000_000;
+        let mut chunks = vec![];
+        let mut n = self.clone();
+        while !n.is_zero() {
+            let (q, r) = n.div_rem_u64(CHUNK);
+            chunks.push(r);
+            n = q;
+        }
+        match chunks.split_last() {
+            None => write!(f, "0"),
+            Some((first, rest)) => {
+                write!(f, "{first}")?;
+                for chunk in rest.iter().rev() {
+                    write!(f, "{chunk:09}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gcd_and_lcm() {
+        assert_eq!(Some(6), gcd(12, 18));
+        assert_eq!(Some(6), gcd(-12, 18));
+        assert_eq!(Some(5), gcd(0, 5));
+        assert_eq!(Some(0), gcd(0u8, 0));
+        assert_eq!(Some(2), gcd(i8::MIN, 6));
+        assert_eq!(Some(64), gcd(i8::MIN, 64));
+        assert_eq!(None, gcd(i8::MIN, 0));
+        assert_eq!(Some(1), gcd(i8::MIN, -1));
+        assert_eq!(None, gcd(i64::MIN, i64::MIN));
+        assert_eq!(None, lcm(i64::MIN, 1));
+        assert_eq!(Some(36), lcm(12, 18));
+        assert_eq!(Some(36), lcm(-12, 18));
+        assert_eq!(Some(0), lcm(0, 18));
+        assert_eq!(None, lcm(200u8, 3));
+    }
+
+    #[test]
+    fn over_iterators() {
+        assert_eq!(Some(4), gcd_all([8, 12, 20]));
+        assert_eq!(Some(0), gcd_all(Vec::<i32>::new()));
+        assert_eq!(None, gcd_all([i32::MIN]));
+        assert_eq!(Some(96577), lcm_all([23u64, 19, 13, 17]));
+        assert_eq!(Some(1), lcm_all(Vec::<u32>::new()));
+        assert_eq!(None, lcm_all([251u8, 241]));
+        let primes = [
+            2u64, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53,
+        ];
+        assert_eq!(None, lcm_all(primes));
+        assert!(lcm_all(primes.map(u128::from)).is_some());
+    }
+
+    #[test]
+    fn mul_mod_does_not_overflow() {
+        assert_eq!(
+            Some(1),
+            checked_mul_mod(u64::MAX - 1, u64::MAX - 1, u64::MAX)
+        );
+        assert_eq!(Some(2), checked_mul_mod(200u8, 200, 7));
+        assert_eq!(Some(1), checked_mul_mod(-3i32, 2, 7));
+        assert_eq!(None, checked_mul_mod(1, 2, 0));
+        assert_eq!(None, checked_mul_mod(1, 2, -5));
+    }
+
+    #[test]
+    fn pow_mod() {
+        assert_eq!(Some(445), checked_pow_mod(4, 13, 497));
+        assert_eq!(Some(0), checked_pow_mod(4, 0, 1));
+        assert_eq!(Some(16), checked_pow_mod(2u64, 1 << 62, (1 << 61) - 1));
+        assert_eq!(None, checked_pow_mod(2, -1, 5));
+    }
+
+    #[test]
+    fn inverse() {
+        assert_eq!(Some(4), mod_inverse(3, 11));
+        assert_eq!(Some(7), mod_inverse(-3, 11));
+        assert_eq!(Some(1969), mod_inverse(42u16, 2017));
+        assert_eq!(None, mod_inverse(6, 9));
+    }
+
+    #[test]
+    fn chinese_remainder() {
+        assert_eq!(Some((23, 105)), crt([(2, 3), (3, 5), (2, 7)]));
+        assert_eq!(Some((10, 12)), crt([(4, 6), (2, 4)]));
+        assert_eq!(None, crt([(1, 6), (2, 4)]));
+        assert_eq!(Some((0, 1)), crt(Vec::<(i64, i64)>::new()));
+        assert_eq!(Some((4, 5)), crt([(-1, 5)]));
+        assert_eq!(None, crt([(1u8, 251), (2, 241)]));
+    }
+
+    #[test]
+    fn big_uint() {
+        let big = |n: u64| BigUint::from(n);
+        let max = big(u64::MAX);
+        let square = &max * &max;
+        assert_eq!(
+            square.to_string(),
+            (u64::MAX as u128 * u64::MAX as u128).to_string()
+        );
+        assert_eq!(square.bits(), 128);
+        assert_eq!(
+            (&square + &big(1)).to_string(),
+            "340282366920938463426481119284349108226"
+        );
+        assert_eq!(square.checked_div_rem(&max), Some((max.clone(), big(0))));
+        let (q, r) = (&square + &big(7))
+            .checked_div_rem(&(&max + &big(2)))
+            .unwrap();
+        assert_eq!(&(&q * &(&max + &big(2))) + &r, &square + &big(7));
+        assert!(r < &max + &big(2));
+        assert_eq!(square.div_rem_u64(u64::MAX), (max.clone(), 0));
+        assert_eq!(big(100).div_rem_u64(7), (big(14), 2));
+        assert_eq!(big(3).checked_sub(&big(5)), None);
+        assert_eq!(square.checked_sub(&square), Some(big(0)));
+        assert_eq!(big(1).checked_div_rem(&big(0)), None);
+        assert_eq!(big(0).to_string(), "0");
+        assert_eq!(big(1_000_000_000).to_string(), "1000000000");
+        assert!(big(5) < square && big(0) < big(1));
+    }
+}
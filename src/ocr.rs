@@ -0,0 +1,142 @@
+//! Reads the capital letters some puzzles draw on a screen, in the 4x6 font
+//! they all share.
+
+use std::fmt;
+
+const HEIGHT: usize = 6;
+// Each glyph is 4 pixels wide, followed by a blank column.
+const CELL_WIDTH: usize = 5;
+
+const FONT: [(char, [&str; HEIGHT]); 17] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OcrError {
+    /// The screen has this many rows instead of 6.
+    Height(usize),
+    /// No letter looks like the glyphs starting at these columns. `partial`
+    /// is everything that could be read, with a `?` for each of them.
+    Unrecognised {
+        columns: Vec<usize>,
+        partial: String,
+    },
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OcrError::Height(rows) => {
+                write!(f, "the screen is {rows} rows high, not {HEIGHT}")
+            }
+            OcrError::Unrecognised { columns, partial } => {
+                let columns: Vec<_> = columns.iter().map(ToString::to_string).collect();
+                write!(
+                    f,
+                    "unrecognised glyphs at columns {} (read {partial:?})",
+                    columns.join(", ")
+                )
+            }
+        }
+    }
+}
+
+/// Reads the letters off `screen`, where `#` is a lit pixel and anything
+/// else is dark. Trailing dark pixels may be left off each line.
+pub fn read_letters(screen: &str) -> Result<String, OcrError> {
+    let rows: Vec<&[u8]> = screen.lines().map(str::as_bytes).collect();
+    if rows.len() != HEIGHT {
+        return Err(OcrError::Height(rows.len()));
+    }
+    let lit = |r: usize, c: usize| rows[r].get(c) == Some(&b'#');
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let mut letters = String::new();
+    let mut unrecognised = vec![];
+    for start in (0..width).step_by(CELL_WIDTH) {
+        let letter = FONT.iter().find(|(_, glyph)| {
+            glyph.iter().enumerate().all(|(r, line)| {
+                line.bytes()
+                    .enumerate()
+                    .all(|(dc, pixel)| lit(r, start + dc) == (pixel == b'#'))
+            })
+        });
+        match letter {
+            Some(&(letter, _)) => letters.push(letter),
+            None => {
+                unrecognised.push(start);
+                letters.push('?');
+            }
+        }
+    }
+    if unrecognised.is_empty() {
+        Ok(letters)
+    } else {
+        Err(OcrError::Unrecognised {
+            columns: unrecognised,
+            partial: letters,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whole_font() {
+        let screen: Vec<String> = (0..HEIGHT)
+            .map(|r| {
+                FONT.iter()
+                    .map(|(_, glyph)| format!("{}.", glyph[r]))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            read_letters(&screen.join("\n")),
+            Ok("ABCEFGHIJKLOPRSUZ".to_string())
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let screen = "\
+###   ##  #  #
+#  # #  # # #
+###  #    ##
+#  # # ## # #
+#  # #  # # #
+###   ### #  #";
+        assert_eq!(read_letters(screen), Ok("BGK".to_string()));
+        let smudged = screen.replacen("##  #", "### #", 1);
+        let error = read_letters(&smudged).unwrap_err();
+        assert_eq!(
+            error,
+            OcrError::Unrecognised {
+                columns: vec![5],
+                partial: "B?K".to_string()
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "unrecognised glyphs at columns 5 (read \"B?K\")"
+        );
+        let short: Vec<_> = screen.lines().take(5).collect();
+        assert_eq!(read_letters(&short.join("\n")), Err(OcrError::Height(5)));
+    }
+}
@@ -0,0 +1,341 @@
+use std::ops::{Bound, Range, RangeBounds, RangeInclusive};
+
+/// An integer type whose values can be stepped through one at a time, which
+/// is what lets a `RangeSet` treat `1..=3` and `4..=6` as one run.
+pub trait Discrete: Copy + Ord {
+    const MIN: Self;
+    const MAX: Self;
+
+    fn succ(self) -> Option<Self>;
+
+    fn pred(self) -> Option<Self>;
+
+    /// The number of steps from `lo` up to `hi`, where `lo <= hi`.
+    fn distance(lo: Self, hi: Self) -> u64;
+}
+
+macro_rules! impl_discrete {
+    ($($t:ty),*) => {
+        $(
+            impl Discrete for $t {
+                const MIN: Self = <$t>::MIN;
+                const MAX: Self = <$t>::MAX;
+
+                fn succ(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+
+                fn pred(self) -> Option<Self> {
+                    self.checked_sub(1)
+                }
+
+                fn distance(lo: Self, hi: Self) -> u64 {
+                    (hi as i128 - lo as i128) as u64
+                }
+            }
+        )*
+    };
+}
+
+impl_discrete!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+/// A set of integers stored as sorted, disjoint runs. Overlapping and
+/// adjacent ranges are merged as they are inserted.
+#[derive(Clone, PartialEq, Eq)]
+pub struct RangeSet<T> {
+    // Inclusive (lo, hi) pairs. Storing the ends inclusively means a range
+    // reaching T::MAX never needs an unrepresentable exclusive end.
+    ranges: Vec<(T, T)>,
+}
+
+impl<T> Default for RangeSet<T> {
+    fn default() -> Self {
+        RangeSet { ranges: vec![] }
+    }
+}
+
+impl<T: Discrete> RangeSet<T> {
+    pub fn new() -> RangeSet<T> {
+        RangeSet::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The number of integers in the set, or `None` if that's too many for
+    /// a `u64`, which only happens when it's every value of a 64-bit type.
+    pub fn len(&self) -> Option<u64> {
+        self.ranges.iter().try_fold(0u64, |len, &(lo, hi)| {
+            T::distance(lo, hi).checked_add(1)?.checked_add(len)
+        })
+    }
+
+    pub fn ranges(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.ranges.iter().map(|&(lo, hi)| lo..=hi)
+    }
+
+    pub fn contains(&self, val: T) -> bool {
+        let i = self.ranges.partition_point(|&(_, hi)| hi < val);
+        i < self.ranges.len() && self.ranges[i].0 <= val
+    }
+
+    pub fn insert(&mut self, range: impl RangeBounds<T>) {
+        let Some((lo, hi)) = to_inclusive(&range) else {
+            return;
+        };
+        // Every range from `start` up to `end` overlaps or touches lo..=hi.
+        let start = self
+            .ranges
+            .partition_point(|&(_, h)| h.succ().is_some_and(|h| h < lo));
+        let end = self
+            .ranges
+            .partition_point(|&(l, _)| l.pred().is_none_or(|l| l <= hi));
+        let mut merged = (lo, hi);
+        if start < end {
+            merged.0 = merged.0.min(self.ranges[start].0);
+            merged.1 = merged.1.max(self.ranges[end - 1].1);
+        }
+        self.ranges.splice(start..end, [merged]);
+    }
+
+    pub fn remove(&mut self, range: impl RangeBounds<T>) {
+        let Some((lo, hi)) = to_inclusive(&range) else {
+            return;
+        };
+        let start = self.ranges.partition_point(|&(_, h)| h < lo);
+        let end = self.ranges.partition_point(|&(l, _)| l <= hi);
+        if start == end {
+            return;
+        }
+        let mut remaining = vec![];
+        let first = self.ranges[start];
+        if first.0 < lo {
+            remaining.push((first.0, lo.pred().unwrap()));
+        }
+        let last = self.ranges[end - 1];
+        if last.1 > hi {
+            remaining.push((hi.succ().unwrap(), last.1));
+        }
+        self.ranges.splice(start..end, remaining);
+    }
+
+    pub fn union(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut result = self.clone();
+        for &(lo, hi) in &other.ranges {
+            result.insert(lo..=hi);
+        }
+        result
+    }
+
+    pub fn intersection(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut ranges = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a_lo, a_hi) = self.ranges[i];
+            let (b_lo, b_hi) = other.ranges[j];
+            let lo = a_lo.max(b_lo);
+            let hi = a_hi.min(b_hi);
+            if lo <= hi {
+                ranges.push((lo, hi));
+            }
+            if a_hi < b_hi {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        RangeSet { ranges }
+    }
+
+    pub fn difference(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut result = self.clone();
+        for &(lo, hi) in &other.ranges {
+            result.remove(lo..=hi);
+        }
+        result
+    }
+
+    /// Everything within `bounds` that is not in the set.
+    pub fn complement(&self, bounds: impl RangeBounds<T>) -> RangeSet<T> {
+        let mut result = RangeSet::new();
+        result.insert(bounds);
+        result.difference(self)
+    }
+
+    /// The uncovered runs within `bounds`, in ascending order.
+    pub fn gaps(&self, bounds: impl RangeBounds<T>) -> impl Iterator<Item = RangeInclusive<T>> {
+        self.complement(bounds)
+            .ranges
+            .into_iter()
+            .map(|(lo, hi)| lo..=hi)
+    }
+
+    pub fn is_disjoint(&self, other: &RangeSet<T>) -> bool {
+        self.intersection(other).is_empty()
+    }
+
+    pub fn is_subset(&self, other: &RangeSet<T>) -> bool {
+        self.difference(other).is_empty()
+    }
+
+    pub fn is_superset(&self, other: &RangeSet<T>) -> bool {
+        other.is_subset(self)
+    }
+}
+
+fn to_inclusive<T: Discrete>(range: &impl RangeBounds<T>) -> Option<(T, T)> {
+    let lo = match range.start_bound() {
+        Bound::Included(&lo) => lo,
+        Bound::Excluded(&lo) => lo.succ()?,
+        Bound::Unbounded => T::MIN,
+    };
+    let hi = match range.end_bound() {
+        Bound::Included(&hi) => hi,
+        Bound::Excluded(&hi) => hi.pred()?,
+        Bound::Unbounded => T::MAX,
+    };
+    (lo <= hi).then_some((lo, hi))
+}
+
+impl<T: Discrete> From<RangeInclusive<T>> for RangeSet<T> {
+    fn from(range: RangeInclusive<T>) -> Self {
+        let mut set = RangeSet::new();
+        set.insert(range);
+        set
+    }
+}
+
+impl<T: Discrete> From<Range<T>> for RangeSet<T> {
+    fn from(range: Range<T>) -> Self {
+        let mut set = RangeSet::new();
+        set.insert(range);
+        set
+    }
+}
+
+impl<T: Discrete, R: RangeBounds<T>> Extend<R> for RangeSet<T> {
+    fn extend<I: IntoIterator<Item = R>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+impl<T: Discrete, R: RangeBounds<T>> FromIterator<R> for RangeSet<T> {
+    fn from_iter<I: IntoIterator<Item = R>>(iter: I) -> Self {
+        let mut set = RangeSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T> std::fmt::Debug for RangeSet<T>
+where
+    T: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set()
+            .entries(self.ranges.iter().map(|(lo, hi)| lo..=hi))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runs(set: &RangeSet<i32>) -> Vec<(i32, i32)> {
+        set.ranges().map(|r| (*r.start(), *r.end())).collect()
+    }
+
+    #[test]
+    fn insert_merges_overlapping_and_adjacent() {
+        let mut set = RangeSet::new();
+        set.insert(1..=3);
+        set.insert(7..10);
+        assert_eq!(vec![(1, 3), (7, 9)], runs(&set));
+        set.insert(4..=5);
+        assert_eq!(vec![(1, 5), (7, 9)], runs(&set));
+        set.insert(6..6);
+        assert_eq!(vec![(1, 5), (7, 9)], runs(&set));
+        set.insert(2..=8);
+        assert_eq!(vec![(1, 9)], runs(&set));
+        assert_eq!(Some(9), set.len());
+    }
+
+    #[test]
+    fn insert_at_type_limits() {
+        let mut set: RangeSet<u8> = RangeSet::new();
+        set.insert(250..);
+        set.insert(..=3);
+        set.insert(4..250);
+        assert_eq!(Some(256), set.len());
+        assert_eq!(1, set.ranges().count());
+    }
+
+    #[test]
+    fn len_of_whole_64_bit_types() {
+        let mut set: RangeSet<u64> = [..=u64::MAX - 1].into_iter().collect();
+        assert_eq!(Some(u64::MAX), set.len());
+        set.insert(u64::MAX..);
+        assert_eq!(None, set.len());
+        let mut split: RangeSet<i64> = [..=-1].into_iter().collect();
+        split.insert(1..);
+        assert_eq!(Some(u64::MAX), split.len());
+        split.insert(0..=0);
+        assert_eq!(None, split.len());
+    }
+
+    #[test]
+    fn remove() {
+        let mut set: RangeSet<i32> = [0..=10, 20..=30].into_iter().collect();
+        set.remove(5..25);
+        assert_eq!(vec![(0, 4), (25, 30)], runs(&set));
+        set.remove(..=0);
+        assert_eq!(vec![(1, 4), (25, 30)], runs(&set));
+        set.remove(..);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn contains() {
+        let set: RangeSet<i32> = [-5..=-1, 3..=4].into_iter().collect();
+        assert!(set.contains(-5));
+        assert!(set.contains(4));
+        assert!(!set.contains(0));
+        assert!(!set.contains(5));
+    }
+
+    #[test]
+    fn set_operations() {
+        let a: RangeSet<i32> = [0..=10, 20..=30].into_iter().collect();
+        let b: RangeSet<i32> = [5..=25].into_iter().collect();
+        assert_eq!(vec![(0, 30)], runs(&a.union(&b)));
+        assert_eq!(vec![(5, 10), (20, 25)], runs(&a.intersection(&b)));
+        assert_eq!(vec![(0, 4), (26, 30)], runs(&a.difference(&b)));
+        assert_eq!(vec![(11, 19)], runs(&b.difference(&a)));
+        assert!(!a.is_disjoint(&b));
+        assert!(RangeSet::from(6..=9).is_subset(&a));
+        assert!(a.is_superset(&RangeSet::from(6..=9)));
+        assert!(!a.is_subset(&b));
+    }
+
+    #[test]
+    fn complement_and_gaps() {
+        let set: RangeSet<i32> = [2..=4, 8..=9].into_iter().collect();
+        assert_eq!(
+            vec![(0, 1), (5, 7), (10, 12)],
+            runs(&set.complement(0..=12))
+        );
+        let gaps: Vec<_> = set.gaps(3..9).collect();
+        assert_eq!(vec![5..=7], gaps);
+        assert_eq!(0, set.gaps(8..=9).count());
+    }
+
+    #[test]
+    fn debug() {
+        let set: RangeSet<i32> = [1..=2, 5..=5].into_iter().collect();
+        assert_eq!("{1..=2, 5..=5}", format!("{set:?}"));
+    }
+}
@@ -0,0 +1,616 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::Grid;
+
+pub type Rgb = [u8; 3];
+
+const BACKGROUND: Rgb = [15, 15, 35];
+const WALL: Rgb = [128, 128, 128];
+// Handed out to every other character in the order they first appear.
+const COLORS: &[Rgb] = &[
+    [255, 215, 0],
+    [0, 200, 255],
+    [255, 80, 80],
+    [80, 220, 100],
+    [230, 120, 255],
+    [255, 150, 40],
+    [255, 255, 255],
+    [120, 120, 255],
+];
+
+/// Collects text snapshots of a simulation, one per frame, and writes them
+/// out as an animation once it's done.
+///
+/// The output format is chosen by the extension of the path it's given:
+/// * `.gif` writes an animated GIF.
+/// * `.cast` writes an asciinema v2 recording of the frames as text.
+/// * `.png` or `.ppm` writes one numbered image per frame, so
+///   `frames/rope.png` becomes `frames/rope-00000.png`,
+///   `frames/rope-00001.png` and so on.
+///
+/// A recorder made with [`Recorder::disabled`] ignores every frame, so
+/// simulations can take one unconditionally.
+pub struct Recorder {
+    output: Option<PathBuf>,
+    frames: Vec<Vec<String>>,
+    colors: Vec<(char, Rgb)>,
+    scale: usize,
+    delay_ms: u32,
+}
+
+impl Recorder {
+    pub fn new(output: impl Into<PathBuf>) -> Recorder {
+        Recorder {
+            output: Some(output.into()),
+            frames: vec![],
+            colors: vec![],
+            scale: 4,
+            delay_ms: 100,
+        }
+    }
+
+    pub fn disabled() -> Recorder {
+        Recorder {
+            output: None,
+            ..Recorder::new("")
+        }
+    }
+
+    /// A recorder writing to the path following `--record` on the command
+    /// line, or a disabled one if there's no such flag.
+    pub fn from_args() -> Recorder {
+        let mut args = std::env::args().skip_while(|arg| arg != "--record");
+        match args.nth(1) {
+            Some(path) => Recorder::new(path),
+            None => Recorder::disabled(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.output.is_some()
+    }
+
+    /// Uses `rgb` for `c` in image output.
+    pub fn with_color(mut self, c: char, rgb: Rgb) -> Recorder {
+        self.colors.retain(|&(existing, _)| existing != c);
+        self.colors.push((c, rgb));
+        self
+    }
+
+    /// Draws each character as a `scale` by `scale` block in image output.
+    pub fn with_scale(mut self, scale: usize) -> Recorder {
+        assert!(scale > 0);
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_delay_ms(mut self, delay_ms: u32) -> Recorder {
+        self.delay_ms = delay_ms;
+        self
+    }
+
+    pub fn num_frames(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Adds a frame drawn by `render`, which is only called when recording.
+    pub fn push(&mut self, render: impl FnOnce() -> String) {
+        if self.is_enabled() {
+            let frame = render();
+            self.frames
+                .push(frame.lines().map(|line| line.to_string()).collect());
+        }
+    }
+
+    pub fn push_grid<T>(&mut self, grid: &Grid<T>, mut cell: impl FnMut(&T) -> char) {
+        self.push(|| {
+            grid.rows()
+                .map(|row| row.map(&mut cell).chain(['\n']).collect::<String>())
+                .collect()
+        });
+    }
+
+    /// Adds a frame covering just the bounding box of `points`, which are
+    /// `(row, col)` positions. Anywhere without a point is `background`, and
+    /// later points are drawn over earlier ones.
+    pub fn push_points(
+        &mut self,
+        points: impl IntoIterator<Item = ((i64, i64), char)>,
+        background: char,
+    ) {
+        self.push(|| {
+            let points: Vec<_> = points.into_iter().collect();
+            let Some(min_r) = points.iter().map(|&((r, _), _)| r).min() else {
+                return String::new();
+            };
+            let max_r = points.iter().map(|&((r, _), _)| r).max().unwrap();
+            let min_c = points.iter().map(|&((_, c), _)| c).min().unwrap();
+            let max_c = points.iter().map(|&((_, c), _)| c).max().unwrap();
+            let width = (max_c - min_c + 1) as usize;
+            let height = (max_r - min_r + 1) as usize;
+            let mut cells = vec![vec![background; width]; height];
+            for ((r, c), ch) in points {
+                cells[(r - min_r) as usize][(c - min_c) as usize] = ch;
+            }
+            cells
+                .into_iter()
+                .map(|row| row.into_iter().chain(['\n']).collect::<String>())
+                .collect()
+        });
+    }
+
+    /// Writes the frames to the output path. Does nothing when disabled.
+    pub fn finish(&self) -> io::Result<()> {
+        let Some(output) = &self.output else {
+            return Ok(());
+        };
+        let extension = output.extension().and_then(|ext| ext.to_str());
+        match extension {
+            Some("gif") => self.write_gif(BufWriter::new(File::create(output)?)),
+            Some("cast") => self.write_cast(BufWriter::new(File::create(output)?)),
+            Some("png") => self.write_frames(output, write_png),
+            Some("ppm") => self.write_frames(output, write_ppm),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("can't tell what format to record {output:?} in"),
+            )),
+        }
+    }
+
+    fn canvas_size(&self) -> (usize, usize) {
+        let rows = self.frames.iter().map(|frame| frame.len()).max();
+        let cols = self
+            .frames
+            .iter()
+            .flatten()
+            .map(|line| line.chars().count())
+            .max();
+        (rows.unwrap_or(0).max(1), cols.unwrap_or(0).max(1))
+    }
+
+    // Every character used in any frame, each with its color. Padding
+    // outside a frame's text is drawn as a space.
+    fn palette(&self) -> Vec<(char, Rgb)> {
+        let mut palette: Vec<(char, Rgb)> = vec![(' ', BACKGROUND)];
+        let mut next_color = COLORS.iter().cycle();
+        for c in self.frames.iter().flatten().flat_map(|line| line.chars()) {
+            if palette.iter().any(|&(p, _)| p == c) {
+                continue;
+            }
+            let rgb = match self.colors.iter().find(|&&(custom, _)| custom == c) {
+                Some(&(_, rgb)) => rgb,
+                None if c == '.' => BACKGROUND,
+                None if c == '#' => WALL,
+                None => *next_color.next().unwrap(),
+            };
+            palette.push((c, rgb));
+        }
+        if let Some(&(_, rgb)) = self.colors.iter().find(|&&(custom, _)| custom == ' ') {
+            palette[0].1 = rgb;
+        }
+        palette
+    }
+
+    // The frame as palette indexes, scaled up and padded to the canvas.
+    fn rasterize(
+        &self,
+        frame: &[String],
+        palette: &[(char, Rgb)],
+        canvas: (usize, usize),
+    ) -> Vec<u32> {
+        let (rows, cols) = canvas;
+        let width = cols * self.scale;
+        let mut pixels = vec![0; rows * self.scale * width];
+        for (r, line) in frame.iter().enumerate() {
+            for (c, ch) in line.chars().enumerate() {
+                // There are fewer chars than u32s, so this always fits.
+                let idx = palette.iter().position(|&(p, _)| p == ch).unwrap() as u32;
+                for y in r * self.scale..(r + 1) * self.scale {
+                    let start = y * width + c * self.scale;
+                    pixels[start..start + self.scale].fill(idx);
+                }
+            }
+        }
+        pixels
+    }
+
+    pub fn write_gif(&self, mut w: impl Write) -> io::Result<()> {
+        let palette = self.palette();
+        if palette.len() > 256 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "a GIF can't have more than 256 colors",
+            ));
+        }
+        let canvas @ (rows, cols) = self.canvas_size();
+        let too_big = |_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "a GIF can't be more than 65535 pixels wide or high",
+            )
+        };
+        let width = u16::try_from(cols * self.scale).map_err(too_big)?;
+        let height = u16::try_from(rows * self.scale).map_err(too_big)?;
+        // The color table size is 2^(n + 1) entries.
+        let table_bits = (palette.len().max(2).next_power_of_two().trailing_zeros() - 1) as u8;
+
+        w.write_all(b"GIF89a")?;
+        w.write_all(&width.to_le_bytes())?;
+        w.write_all(&height.to_le_bytes())?;
+        w.write_all(&[0x80 | table_bits, 0, 0])?;
+        for i in 0..2 << table_bits {
+            let rgb = palette.get(i).map_or([0; 3], |&(_, rgb)| rgb);
+            w.write_all(&rgb)?;
+        }
+        // Loop forever.
+        w.write_all(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00")?;
+        let delay = (self.delay_ms / 10) as u16;
+        let min_code_size = (table_bits + 1).max(2);
+        for frame in &self.frames {
+            w.write_all(&[0x21, 0xf9, 4, 0])?;
+            w.write_all(&delay.to_le_bytes())?;
+            w.write_all(&[0, 0])?;
+            w.write_all(&[0x2c, 0, 0, 0, 0])?;
+            w.write_all(&width.to_le_bytes())?;
+            w.write_all(&height.to_le_bytes())?;
+            w.write_all(&[0, min_code_size])?;
+            // The palette has at most 256 colors, so every index fits.
+            let pixels: Vec<u8> = self
+                .rasterize(frame, &palette, canvas)
+                .into_iter()
+                .map(|idx| idx as u8)
+                .collect();
+            let data = lzw_encode(&pixels, min_code_size);
+            for block in data.chunks(255) {
+                w.write_all(&[block.len() as u8])?;
+                w.write_all(block)?;
+            }
+            w.write_all(&[0])?;
+        }
+        w.write_all(&[0x3b])?;
+        w.flush()
+    }
+
+    pub fn write_cast(&self, mut w: impl Write) -> io::Result<()> {
+        let (rows, cols) = self.canvas_size();
+        writeln!(
+            w,
+            "{{\"version\": 2, \"width\": {cols}, \"height\": {rows}}}"
+        )?;
+        for (i, frame) in self.frames.iter().enumerate() {
+            let time = i as f64 * self.delay_ms as f64 / 1000.0;
+            // Clear the screen, then draw the frame from the top left.
+            let text = format!("\x1b[H\x1b[2J{}", frame.join("\r\n"));
+            writeln!(w, "[{time:.3}, \"o\", {}]", json_string(&text))?;
+        }
+        w.flush()
+    }
+
+    fn write_frames(&self, output: &Path, write_image: ImageWriter) -> io::Result<()> {
+        if let Some(dir) = output.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let stem = output.file_stem().unwrap().to_string_lossy();
+        let extension = output.extension().unwrap().to_string_lossy();
+        let palette = self.palette();
+        let canvas @ (rows, cols) = self.canvas_size();
+        let (width, height) = (cols * self.scale, rows * self.scale);
+        for (i, frame) in self.frames.iter().enumerate() {
+            let rgb: Vec<u8> = self
+                .rasterize(frame, &palette, canvas)
+                .into_iter()
+                .flat_map(|idx| palette[idx as usize].1)
+                .collect();
+            let path = output.with_file_name(format!("{stem}-{i:05}.{extension}"));
+            let mut w = BufWriter::new(File::create(path)?);
+            write_image(width, height, &rgb, &mut w)?;
+            w.flush()?;
+        }
+        Ok(())
+    }
+}
+
+type ImageWriter = fn(usize, usize, &[u8], &mut dyn Write) -> io::Result<()>;
+
+fn write_ppm(width: usize, height: usize, rgb: &[u8], w: &mut dyn Write) -> io::Result<()> {
+    write!(w, "P6\n{width} {height}\n255\n")?;
+    w.write_all(rgb)
+}
+
+fn write_png(width: usize, height: usize, rgb: &[u8], w: &mut dyn Write) -> io::Result<()> {
+    w.write_all(b"\x89PNG\r\n\x1a\n")?;
+    let mut header = vec![];
+    header.extend((width as u32).to_be_bytes());
+    header.extend((height as u32).to_be_bytes());
+    // 8 bits per channel, RGB, default compression, filtering and no
+    // interlacing.
+    header.extend([8, 2, 0, 0, 0]);
+    write_png_chunk(w, b"IHDR", &header)?;
+    // Each scanline starts with filter type 0 (none).
+    let mut scanlines = Vec::with_capacity(height * (width * 3 + 1));
+    for row in rgb.chunks(width * 3) {
+        scanlines.push(0);
+        scanlines.extend_from_slice(row);
+    }
+    write_png_chunk(w, b"IDAT", &zlib_stored(&scanlines))?;
+    write_png_chunk(w, b"IEND", &[])
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+// The variable-width LZW flavour GIF uses, packed least significant bit
+// first.
+fn lzw_encode(pixels: &[u8], min_code_size: u8) -> Vec<u8> {
+    const MAX_CODE: u16 = 4096;
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+    let mut out = vec![];
+    let mut bits: u32 = 0;
+    let mut num_bits = 0;
+    let mut emit = |code: u16, code_size: u8| {
+        bits |= (code as u32) << num_bits;
+        num_bits += code_size;
+        while num_bits >= 8 {
+            out.push(bits as u8);
+            bits >>= 8;
+            num_bits -= 8;
+        }
+    };
+    let mut table = std::collections::HashMap::new();
+    let mut code_size = min_code_size + 1;
+    let mut next_code = end + 1;
+    emit(clear, code_size);
+    let mut pixels = pixels.iter().copied();
+    if let Some(first) = pixels.next() {
+        let mut prefix = first as u16;
+        for k in pixels {
+            if let Some(&code) = table.get(&(prefix, k)) {
+                prefix = code;
+                continue;
+            }
+            emit(prefix, code_size);
+            if next_code < MAX_CODE {
+                table.insert((prefix, k), next_code);
+                next_code += 1;
+                if next_code > 1 << code_size && code_size < 12 {
+                    code_size += 1;
+                }
+            } else {
+                emit(clear, code_size);
+                table.clear();
+                code_size = min_code_size + 1;
+                next_code = end + 1;
+            }
+            prefix = k as u16;
+        }
+        emit(prefix, code_size);
+    }
+    emit(end, code_size);
+    if num_bits > 0 {
+        out.push(bits as u8);
+    }
+    out
+}
+
+fn write_png_chunk(w: &mut dyn Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    w.write_all(&(data.len() as u32).to_be_bytes())?;
+    w.write_all(kind)?;
+    w.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data).copied());
+    w.write_all(&crc.to_be_bytes())
+}
+
+fn crc32(bytes: impl Iterator<Item = u8>) -> u32 {
+    let mut crc = !0u32;
+    for b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb88320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+// A zlib stream using only uncompressed deflate blocks, which is all a PNG
+// strictly needs.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let is_final = blocks.peek().is_none() as u8;
+        let len = block.len() as u16;
+        out.push(is_final);
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    out.extend(((b << 16) | a).to_be_bytes());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lzw_decode(data: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear = 1u16 << min_code_size;
+        let end = clear + 1;
+        let mut table: Vec<Vec<u8>> = vec![];
+        let reset = |table: &mut Vec<Vec<u8>>| {
+            *table = (0..clear).map(|i| vec![i as u8]).collect();
+            table.push(vec![]);
+            table.push(vec![]);
+        };
+        reset(&mut table);
+        let mut code_size = min_code_size + 1;
+        let (mut bits, mut num_bits, mut pos) = (0u32, 0, 0);
+        let mut prev: Option<Vec<u8>> = None;
+        let mut out = vec![];
+        loop {
+            while num_bits < code_size {
+                bits |= (data[pos] as u32) << num_bits;
+                pos += 1;
+                num_bits += 8;
+            }
+            let code = (bits & ((1 << code_size) - 1)) as u16;
+            bits >>= code_size;
+            num_bits -= code_size;
+            if code == clear {
+                reset(&mut table);
+                code_size = min_code_size + 1;
+                prev = None;
+                continue;
+            }
+            if code == end {
+                return out;
+            }
+            let entry = match (table.get(code as usize), &prev) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(prev)) => {
+                    let mut entry = prev.clone();
+                    entry.push(prev[0]);
+                    entry
+                }
+                (None, None) => panic!("bad code"),
+            };
+            out.extend(&entry);
+            if let Some(prev) = prev {
+                if table.len() < 4096 {
+                    let mut new_entry = prev;
+                    new_entry.push(entry[0]);
+                    table.push(new_entry);
+                    if table.len() == 1 << code_size && code_size < 12 {
+                        code_size += 1;
+                    }
+                }
+            }
+            prev = Some(entry);
+        }
+    }
+
+    #[test]
+    fn lzw_round_trip() {
+        let pixels: Vec<u8> = (0..20000u32).map(|i| ((i * i) / 7 % 5) as u8).collect();
+        assert_eq!(pixels, lzw_decode(&lzw_encode(&pixels, 3), 3));
+        let pixels = vec![1; 10000];
+        assert_eq!(pixels, lzw_decode(&lzw_encode(&pixels, 2), 2));
+        assert!(lzw_decode(&lzw_encode(&[], 2), 2).is_empty());
+    }
+
+    #[test]
+    fn lzw_known_output() {
+        // The 3x5 sample image from Wikipedia's GIF article.
+        let mut pixels = vec![0x28, 0xff, 0xff, 0xff, 0x28];
+        pixels.extend([0xff; 10]);
+        assert_eq!(
+            [0x00, 0x51, 0xfc, 0x1b, 0x28, 0x70, 0xa0, 0xc1, 0x83, 0x01, 0x01],
+            lzw_encode(&pixels, 8)[..]
+        );
+        // The image data of the well-known smallest 1x1 GIF.
+        assert_eq!([0x44, 0x01], lzw_encode(&[0], 2)[..]);
+    }
+
+    #[test]
+    fn gif_frames() {
+        let mut recorder = Recorder::new("out.gif").with_scale(2);
+        recorder.push(|| "#.\n.o\n".to_string());
+        recorder.push(|| "o".to_string());
+        let mut gif = vec![];
+        recorder.write_gif(&mut gif).unwrap();
+        assert!(gif.starts_with(b"GIF89a\x04\x00\x04\x00"));
+        assert_eq!(Some(&0x3b), gif.last());
+    }
+
+    #[test]
+    fn gif_limits() {
+        let mut recorder = Recorder::new("out.gif").with_scale(65536);
+        recorder.push(|| "#".to_string());
+        let error = recorder.write_gif(&mut vec![]).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+        let mut recorder = Recorder::new("out.gif");
+        recorder.push(|| ('\u{100}'..='\u{200}').collect());
+        let error = recorder.write_gif(&mut vec![]).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+    }
+
+    #[test]
+    fn disabled_ignores_frames() {
+        let mut recorder = Recorder::disabled();
+        recorder.push(|| panic!("shouldn't render"));
+        assert_eq!(0, recorder.num_frames());
+        assert!(recorder.finish().is_ok());
+    }
+
+    #[test]
+    fn points() {
+        let mut recorder = Recorder::new("out.cast");
+        recorder.push_points([((-1, 3), 'H'), ((1, 2), 'T'), ((1, 3), '#')], '.');
+        assert_eq!(vec![".H", "..", "T#"], recorder.frames[0]);
+    }
+
+    #[test]
+    fn cast() {
+        let mut recorder = Recorder::new("out.cast").with_delay_ms(500);
+        recorder.push(|| "a\"b\nc".to_string());
+        recorder.push(|| "d".to_string());
+        let mut cast = vec![];
+        recorder.write_cast(&mut cast).unwrap();
+        assert_eq!(
+            "{\"version\": 2, \"width\": 3, \"height\": 2}\n\
+             [0.000, \"o\", \"\\u001b[H\\u001b[2Ja\\\"b\\r\\nc\"]\n\
+             [0.500, \"o\", \"\\u001b[H\\u001b[2Jd\"]\n",
+            String::from_utf8(cast).unwrap()
+        );
+    }
+
+    #[test]
+    fn image_frames() {
+        let dir = std::env::temp_dir().join(format!("aoc-record-{}", std::process::id()));
+        let mut recorder = Recorder::new(dir.join("f.ppm"))
+            .with_scale(1)
+            .with_color('x', [1, 2, 3]);
+        recorder.push(|| "x.".to_string());
+        recorder.push(|| ".".to_string());
+        recorder.finish().unwrap();
+        let mut expected = b"P6\n2 1\n255\n\x01\x02\x03".to_vec();
+        expected.extend(BACKGROUND);
+        assert_eq!(expected, std::fs::read(dir.join("f-00000.ppm")).unwrap());
+        assert!(dir.join("f-00001.ppm").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn png_checksums() {
+        assert_eq!(0xcbf43926, crc32(b"123456789".iter().copied()));
+        let zlib = zlib_stored(b"Wikipedia");
+        assert_eq!([0x11, 0xe6, 0x03, 0x98], zlib[zlib.len() - 4..]);
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A maximisation problem explored depth-first by [`maximize`].
+pub trait BranchAndBound {
+    type State: Clone;
+    type Score: Copy + Ord;
+    type Key: Hash + Eq;
+
+    /// The score of stopping at this state.
+    fn score(&self, state: &Self::State) -> Self::Score;
+
+    /// An upper bound on the score of this state and every state reachable
+    /// from it. It must never underestimate, or the best state may be pruned.
+    fn upper_bound(&self, state: &Self::State) -> Self::Score;
+
+    /// The states reachable in one step. They're explored in order, so
+    /// putting the most promising first lets the bound prune more.
+    fn successors(&self, state: &Self::State) -> Vec<Self::State>;
+
+    /// A key for the transposition table, or `None` to not memoise this
+    /// state. Two states with the same key must have the same successors
+    /// apart from their scores, which differ by the same amount, so that the
+    /// lower scoring one can be skipped.
+    fn key(&self, _state: &Self::State) -> Option<Self::Key> {
+        None
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    /// States whose successors were generated.
+    pub expanded: usize,
+    /// States skipped because of the bound or the transposition table.
+    pub pruned: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Solution<S, V> {
+    pub best: S,
+    pub score: V,
+    /// Every state from the start up to and including `best`.
+    pub path: Vec<S>,
+    pub stats: Stats,
+}
+
+/// Finds the highest scoring state reachable from `start`.
+pub fn maximize<P: BranchAndBound>(problem: &P, start: P::State) -> Solution<P::State, P::Score> {
+    let mut search = Search {
+        problem,
+        best_score: problem.score(&start),
+        best_path: vec![start.clone()],
+        path: vec![],
+        seen: HashMap::new(),
+        stats: Stats::default(),
+    };
+    search.visit(start);
+    let best = search.best_path.last().unwrap().clone();
+    Solution {
+        best,
+        score: search.best_score,
+        path: search.best_path,
+        stats: search.stats,
+    }
+}
+
+struct Search<'a, P: BranchAndBound> {
+    problem: &'a P,
+    best_score: P::Score,
+    best_path: Vec<P::State>,
+    path: Vec<P::State>,
+    seen: HashMap<P::Key, P::Score>,
+    stats: Stats,
+}
+
+impl<'a, P: BranchAndBound> Search<'a, P> {
+    fn visit(&mut self, state: P::State) {
+        let score = self.problem.score(&state);
+        if let Some(key) = self.problem.key(&state) {
+            match self.seen.get_mut(&key) {
+                Some(seen_score) if *seen_score >= score => {
+                    self.stats.pruned += 1;
+                    return;
+                }
+                Some(seen_score) => *seen_score = score,
+                None => {
+                    self.seen.insert(key, score);
+                }
+            }
+        }
+        self.path.push(state);
+        if score > self.best_score {
+            self.best_score = score;
+            self.best_path = self.path.clone();
+        }
+        let state = self.path.last().unwrap();
+        if self.problem.upper_bound(state) <= self.best_score {
+            self.stats.pruned += 1;
+        } else {
+            self.stats.expanded += 1;
+            for next in self.problem.successors(state) {
+                self.visit(next);
+            }
+        }
+        self.path.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0/1 knapsack: state is (next item, weight used, value so far).
+    struct Knapsack {
+        items: Vec<(u32, u32)>,
+        capacity: u32,
+    }
+
+    impl BranchAndBound for Knapsack {
+        type State = (usize, u32, u32);
+        type Score = u32;
+        type Key = (usize, u32);
+
+        fn score(&self, &(_, _, value): &Self::State) -> u32 {
+            value
+        }
+
+        fn upper_bound(&self, &(i, _, value): &Self::State) -> u32 {
+            value + self.items[i..].iter().map(|&(_, v)| v).sum::<u32>()
+        }
+
+        fn successors(&self, &(i, weight, value): &Self::State) -> Vec<Self::State> {
+            let Some(&(w, v)) = self.items.get(i) else {
+                return vec![];
+            };
+            let mut next = vec![];
+            if weight + w <= self.capacity {
+                next.push((i + 1, weight + w, value + v));
+            }
+            next.push((i + 1, weight, value));
+            next
+        }
+
+        fn key(&self, &(i, weight, _): &Self::State) -> Option<Self::Key> {
+            Some((i, weight))
+        }
+    }
+
+    #[test]
+    fn knapsack() {
+        let problem = Knapsack {
+            items: vec![(5, 10), (4, 40), (6, 30), (3, 50)],
+            capacity: 10,
+        };
+        let solution = maximize(&problem, (0, 0, 0));
+        assert_eq!(90, solution.score);
+        assert_eq!((4, 7, 90), solution.best);
+        let weights: Vec<_> = solution.path.iter().map(|&(_, w, _)| w).collect();
+        assert_eq!(vec![0, 0, 4, 4, 7], weights);
+        assert!(solution.stats.pruned > 0);
+    }
+
+    #[test]
+    fn start_is_best() {
+        let problem = Knapsack {
+            items: vec![(5, 10)],
+            capacity: 1,
+        };
+        let solution = maximize(&problem, (0, 0, 0));
+        assert_eq!(0, solution.score);
+        assert_eq!(vec![(0, 0, 0)], solution.path);
+    }
+}